@@ -383,6 +383,7 @@ pub(crate) fn create_selection_export_prefs_from_args(
         bitmap_scalefactor,
         jpeg_quality,
         margin,
+        ..Default::default()
     };
 
     Ok(prefs)
@@ -393,6 +394,8 @@ fn get_selection_export_format(format: &str) -> anyhow::Result<SelectionExportFo
         "svg" => Ok(SelectionExportFormat::Svg),
         "png" => Ok(SelectionExportFormat::Png),
         "jpg" | "jpeg" => Ok(SelectionExportFormat::Jpeg),
+        "webp" => Ok(SelectionExportFormat::WebP),
+        "avif" => Ok(SelectionExportFormat::Avif),
         ext => Err(anyhow::anyhow!(
             "Exporting selection to format with extension \"{ext}\" is not supported."
         )),
@@ -13,6 +13,7 @@ use crate::transform::Transformable;
 use kurbo::Shape;
 use p2d::bounding_volume::{Aabb, BoundingVolume};
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename = "pen_path")]
@@ -117,6 +118,47 @@ impl PenPath {
         }
     }
 
+    /// The end element of the path, which is the start when the path has no segments.
+    pub fn end(&self) -> Element {
+        self.segments
+            .last()
+            .map_or(self.start, |segment| segment.end())
+    }
+
+    /// The same path shape, but running from the end to the start.
+    pub fn reversed(&self) -> Self {
+        let mut prev = self.start;
+        let mut segments = self
+            .segments
+            .iter()
+            .map(|segment| {
+                let reversed = match *segment {
+                    Segment::LineTo { .. } => Segment::LineTo { end: prev },
+                    Segment::QuadBezTo { cp, .. } => Segment::QuadBezTo { cp, end: prev },
+                    Segment::CubBezTo { cp1, cp2, .. } => Segment::CubBezTo {
+                        cp1: cp2,
+                        cp2: cp1,
+                        end: prev,
+                    },
+                };
+                prev = segment.end();
+                reversed
+            })
+            .collect::<Vec<Segment>>();
+        segments.reverse();
+
+        Self {
+            start: prev,
+            segments,
+        }
+    }
+
+    /// Append the other path, connecting the end of this path to the start of the other path with a line.
+    pub fn append(&mut self, other: PenPath) {
+        self.segments.push(Segment::LineTo { end: other.start });
+        self.segments.extend(other.segments);
+    }
+
     /// extracts the elements from the path. the path shape will be lost, as only the actual input elements are returned.
     pub fn into_elements(self) -> Vec<Element> {
         let mut elements = vec![self.start];
@@ -142,6 +184,70 @@ impl PenPath {
         Some(Self { start, segments })
     }
 
+    /// Simplify the path with the Douglas-Peucker algorithm, removing elements that deviate from the simplified path
+    /// by less than the tolerance.
+    ///
+    /// Only consecutive line-to segments are simplified, bezier segments are kept as they are.
+    /// A tolerance of zero returns the path unchanged.
+    pub fn simplified(&self, tolerance: f64) -> Self {
+        if tolerance <= 0.0 {
+            return self.clone();
+        }
+        let mut segments = Vec::with_capacity(self.segments.len());
+        let mut run_start = self.start;
+        let mut run = Vec::new();
+
+        for &seg in self.segments.iter() {
+            match seg {
+                Segment::LineTo { end } => run.push(end),
+                Segment::QuadBezTo { end, .. } | Segment::CubBezTo { end, .. } => {
+                    segments.extend(simplify_line_run(run_start, &run, tolerance));
+                    run.clear();
+                    segments.push(seg);
+                    run_start = end;
+                }
+            }
+        }
+        segments.extend(simplify_line_run(run_start, &run, tolerance));
+
+        Self {
+            start: self.start,
+            segments,
+        }
+    }
+
+    /// Smooth the path by replacing every element position with the average of the positions
+    /// of the `radius` elements before and after it, reducing jitter while preserving the overall shape.
+    ///
+    /// Like [PenPath::simplified] only consecutive line-to segments are smoothed, their first and last element
+    /// and the bezier segments are kept as they are. The pressures are untouched. A radius of zero returns the path unchanged.
+    pub fn smoothed(&self, radius: usize) -> Self {
+        if radius == 0 {
+            return self.clone();
+        }
+        let mut segments = Vec::with_capacity(self.segments.len());
+        let mut run_start = self.start;
+        let mut run = Vec::new();
+
+        for &seg in self.segments.iter() {
+            match seg {
+                Segment::LineTo { end } => run.push(end),
+                Segment::QuadBezTo { end, .. } | Segment::CubBezTo { end, .. } => {
+                    segments.extend(smooth_line_run(run_start, &run, radius));
+                    run.clear();
+                    segments.push(seg);
+                    run_start = end;
+                }
+            }
+        }
+        segments.extend(smooth_line_run(run_start, &run, radius));
+
+        Self {
+            start: self.start,
+            segments,
+        }
+    }
+
     /// Checks whether bounds collide with the path. If it does, it returns the indices of the colliding segments
     ///
     /// `loosened` loosens the segments hitboxes by the value
@@ -157,8 +263,26 @@ impl PenPath {
             .collect()
     }
 
+    /// The hitboxes of each segment in the given range of segment indices, clamped to the existing segments.
+    pub fn hitboxes_for_segments(&self, range: Range<usize>) -> Vec<Vec<Aabb>> {
+        let range = range.start.min(self.segments.len())..range.end.min(self.segments.len());
+        let mut prev = range
+            .start
+            .checked_sub(1)
+            .map(|i| self.segments[i].end())
+            .unwrap_or(self.start);
+
+        self.segments[range]
+            .iter()
+            .map(|seg| {
+                let seg_hitboxes = segment_hitboxes(prev, seg);
+                prev = seg.end();
+                seg_hitboxes
+            })
+            .collect()
+    }
+
     fn hitboxes_w_segs_indices(&self) -> Vec<(Option<usize>, Vec<Aabb>)> {
-        let mut hitboxes = Vec::with_capacity(self.segments.len());
         if self.segments.is_empty() {
             return vec![(
                 None,
@@ -169,70 +293,11 @@ impl PenPath {
             )];
         }
 
-        let mut prev = self.start;
-        for (i, seg) in self.segments.iter().enumerate() {
-            match seg {
-                Segment::LineTo { end } => {
-                    let n_splits = no_subsegments_for_segment_len((end.pos - prev.pos).magnitude());
-                    let line = Line {
-                        start: prev.pos,
-                        end: end.pos,
-                    };
-
-                    hitboxes.push((
-                        Some(i),
-                        line.split(n_splits)
-                            .into_iter()
-                            .map(|line| line.bounds())
-                            .collect(),
-                    ));
-                    prev = *end;
-                }
-                Segment::QuadBezTo { cp, end } => {
-                    let quadbez = QuadraticBezier {
-                        start: prev.pos,
-                        cp: *cp,
-                        end: end.pos,
-                    };
-
-                    let n_splits =
-                        no_subsegments_for_segment_len(quadbez.outline_path().perimeter(0.25));
-
-                    hitboxes.push((
-                        Some(i),
-                        quadbez
-                            .approx_with_lines(n_splits)
-                            .into_iter()
-                            .map(|line| line.bounds())
-                            .collect(),
-                    ));
-                    prev = *end;
-                }
-                Segment::CubBezTo { cp1, cp2, end } => {
-                    let cubbez = CubicBezier {
-                        start: prev.pos,
-                        cp1: *cp1,
-                        cp2: *cp2,
-                        end: end.pos,
-                    };
-
-                    let n_splits =
-                        no_subsegments_for_segment_len(cubbez.outline_path().perimeter(0.25));
-
-                    hitboxes.push((
-                        Some(i),
-                        cubbez
-                            .approx_with_lines(n_splits)
-                            .into_iter()
-                            .map(|line| line.bounds())
-                            .collect(),
-                    ));
-                    prev = *end;
-                }
-            }
-        }
-
-        hitboxes
+        self.hitboxes_for_segments(0..self.segments.len())
+            .into_iter()
+            .enumerate()
+            .map(|(i, seg_hitboxes)| (Some(i), seg_hitboxes))
+            .collect()
     }
 
     /// Convert to [kurbo::BezPath], flattened to the given precision.
@@ -268,6 +333,145 @@ impl Extend<Segment> for PenPath {
     }
 }
 
+/// Simplify a run of line-to segment ends following the start element with the Douglas-Peucker algorithm.
+///
+/// The last element of the run is always kept.
+fn simplify_line_run(start: Element, run: &[Element], tolerance: f64) -> Vec<Segment> {
+    let Some(last) = run.len().checked_sub(1) else {
+        return vec![];
+    };
+    let points = std::iter::once(&start)
+        .chain(run.iter())
+        .map(|element| element.pos)
+        .collect::<Vec<na::Vector2<f64>>>();
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[last + 1] = true;
+
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((first, end)) = ranges.pop() {
+        let (max_index, max_dist) = (first + 1..end)
+            .map(|i| {
+                (
+                    i,
+                    point_line_segment_dist(points[i], points[first], points[end]),
+                )
+            })
+            .fold(
+                (first, 0.0),
+                |acc, (i, dist)| {
+                    if dist > acc.1 {
+                        (i, dist)
+                    } else {
+                        acc
+                    }
+                },
+            );
+        if max_dist > tolerance {
+            keep[max_index] = true;
+            ranges.push((first, max_index));
+            ranges.push((max_index, end));
+        }
+    }
+
+    run.iter()
+        .zip(keep.into_iter().skip(1))
+        .filter(|(_, keep)| *keep)
+        .map(|(&end, _)| Segment::LineTo { end })
+        .collect()
+}
+
+/// Smooth the run of line-to elements following the start with a moving average over `radius` neighbours on each side.
+///
+/// The start and the last element of the run stay in place.
+fn smooth_line_run(start: Element, run: &[Element], radius: usize) -> Vec<Segment> {
+    let points = std::iter::once(&start)
+        .chain(run.iter())
+        .map(|element| element.pos)
+        .collect::<Vec<na::Vector2<f64>>>();
+    let last = points.len() - 1;
+
+    run.iter()
+        .enumerate()
+        .map(|(i, &element)| {
+            // index into points, which is offset by the start
+            let i = i + 1;
+            if i == last {
+                return Segment::LineTo { end: element };
+            }
+            let window = &points[i.saturating_sub(radius)..=(i + radius).min(last)];
+            let pos = window.iter().sum::<na::Vector2<f64>>() / window.len() as f64;
+            Segment::LineTo {
+                end: Element { pos, ..element },
+            }
+        })
+        .collect()
+}
+
+/// The distance of the point to the line segment from a to b.
+fn point_line_segment_dist(
+    point: na::Vector2<f64>,
+    a: na::Vector2<f64>,
+    b: na::Vector2<f64>,
+) -> f64 {
+    let ab = b - a;
+    let len_squared = ab.norm_squared();
+    if len_squared <= 0.0 {
+        return (point - a).norm();
+    }
+    let t = ((point - a).dot(&ab) / len_squared).clamp(0.0, 1.0);
+    (point - (a + ab * t)).norm()
+}
+
+/// The hitboxes of a single segment, starting at the given previous element.
+fn segment_hitboxes(prev: Element, seg: &Segment) -> Vec<Aabb> {
+    match seg {
+        Segment::LineTo { end } => {
+            let n_splits = no_subsegments_for_segment_len((end.pos - prev.pos).magnitude());
+            let line = Line {
+                start: prev.pos,
+                end: end.pos,
+            };
+
+            line.split(n_splits)
+                .into_iter()
+                .map(|line| line.bounds())
+                .collect()
+        }
+        Segment::QuadBezTo { cp, end } => {
+            let quadbez = QuadraticBezier {
+                start: prev.pos,
+                cp: *cp,
+                end: end.pos,
+            };
+
+            let n_splits = no_subsegments_for_segment_len(quadbez.outline_path().perimeter(0.25));
+
+            quadbez
+                .approx_with_lines(n_splits)
+                .into_iter()
+                .map(|line| line.bounds())
+                .collect()
+        }
+        Segment::CubBezTo { cp1, cp2, end } => {
+            let cubbez = CubicBezier {
+                start: prev.pos,
+                cp1: *cp1,
+                cp2: *cp2,
+                end: end.pos,
+            };
+
+            let n_splits = no_subsegments_for_segment_len(cubbez.outline_path().perimeter(0.25));
+
+            cubbez
+                .approx_with_lines(n_splits)
+                .into_iter()
+                .map(|line| line.bounds())
+                .collect()
+        }
+    }
+}
+
 /// Calculates the number subsegment elements (for hitboxes/ flattening of bezier curve)
 /// for the given segment length, capped with a maximum no of hitbox elements
 pub(crate) fn no_subsegments_for_segment_len(len: f64) -> i32 {
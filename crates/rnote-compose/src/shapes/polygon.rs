@@ -90,6 +90,78 @@ impl Polygon {
             path: Vec::new(),
         }
     }
+
+    /// Whether any two non-adjacent edges of the polygon cross each other.
+    pub fn self_intersects(&self) -> bool {
+        self.crossing_edges().is_some()
+    }
+
+    /// Untangle a self-intersecting polygon, so that it becomes a simple polygon with the same vertices.
+    ///
+    /// Crossing edges are repeatedly swapped by reversing the vertices between them,
+    /// which shortens the outline every time, so the fill isn't broken up into overlapping loops anymore.
+    /// Returns whether the polygon was changed.
+    pub fn untangle(&mut self) -> bool {
+        let mut vertices = self.vertices();
+        // every swap shortens the outline, this only guards against floating point edge cases
+        let max_swaps = vertices.len().pow(2);
+        let mut changed = false;
+
+        for _ in 0..max_swaps {
+            let Some((i, j)) = crossing_edges(&vertices) else {
+                break;
+            };
+            vertices[i + 1..=j].reverse();
+            changed = true;
+        }
+        if changed {
+            self.start = vertices[0];
+            self.path = vertices.split_off(1);
+        }
+        changed
+    }
+
+    fn vertices(&self) -> Vec<na::Vector2<f64>> {
+        std::iter::once(self.start)
+            .chain(self.path.iter().copied())
+            .collect()
+    }
+
+    fn crossing_edges(&self) -> Option<(usize, usize)> {
+        crossing_edges(&self.vertices())
+    }
+}
+
+/// The indices of the first pair of non-adjacent crossing edges of the closed polygon with the given vertices.
+///
+/// Edge `i` goes from vertex `i` to the next vertex, the first returned index is always smaller.
+fn crossing_edges(vertices: &[na::Vector2<f64>]) -> Option<(usize, usize)> {
+    let n = vertices.len();
+    if n < 4 {
+        return None;
+    }
+    let edge = |i: usize| (vertices[i], vertices[(i + 1) % n]);
+
+    (0..n).find_map(|i| {
+        (i + 2..n)
+            // the first and the last edge are adjacent
+            .filter(|&j| !(i == 0 && j == n - 1))
+            .find(|&j| segments_cross(edge(i), edge(j)))
+            .map(|j| (i, j))
+    })
+}
+
+/// Whether the segments properly cross each other, touching end points don't count.
+fn segments_cross(
+    (a, b): (na::Vector2<f64>, na::Vector2<f64>),
+    (c, d): (na::Vector2<f64>, na::Vector2<f64>),
+) -> bool {
+    let orientation =
+        |p: na::Vector2<f64>, q: na::Vector2<f64>, r: na::Vector2<f64>| (q - p).perp(&(r - p));
+    let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+    let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+
+    o1 * o2 < 0.0 && o3 * o4 < 0.0
 }
 
 impl Extend<na::Vector2<f64>> for Polygon {
@@ -21,6 +21,21 @@ pub enum NudgeDirection {
     NorthWest,
 }
 
+/// A coordinate space that positions and bounds can be expressed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordSpace {
+    /// Document coordinates, independent of the camera.
+    Document,
+    /// Surface coordinates, the document coordinates transformed by the camera zoom and offset.
+    Surface,
+}
+
+impl Default for CoordSpace {
+    fn default() -> Self {
+        Self::Document
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename = "camera")]
 pub struct Camera {
@@ -278,11 +293,76 @@ impl Camera {
         bounds.scale(self.total_zoom()).translate(-self.offset)
     }
 
+    /// Transform Aabb from document coords into the given coordinate space.
+    pub fn bounds_in(&self, bounds: Aabb, space: CoordSpace) -> Aabb {
+        self.convert_bounds(bounds, CoordSpace::Document, space)
+    }
+
+    /// The transform from document coords into the given coordinate space.
+    pub fn transform_to(&self, space: CoordSpace) -> na::Affine2<f64> {
+        match space {
+            CoordSpace::Document => na::Affine2::identity(),
+            CoordSpace::Surface => self.transform(),
+        }
+    }
+
+    /// The scale factor from document coords into the given coordinate space.
+    pub fn scale_to(&self, space: CoordSpace) -> f64 {
+        match space {
+            CoordSpace::Document => 1.0,
+            CoordSpace::Surface => self.total_zoom(),
+        }
+    }
+
+    /// Convert a point between coordinate spaces.
+    pub fn convert_point(
+        &self,
+        p: na::Point2<f64>,
+        from: CoordSpace,
+        to: CoordSpace,
+    ) -> na::Point2<f64> {
+        let doc_p = self.transform_to(from).inverse_transform_point(&p);
+        self.transform_to(to).transform_point(&doc_p)
+    }
+
+    /// Convert Aabb between coordinate spaces.
+    pub fn convert_bounds(&self, bounds: Aabb, from: CoordSpace, to: CoordSpace) -> Aabb {
+        Aabb::new(
+            self.convert_point(bounds.mins, from, to),
+            self.convert_point(bounds.maxs, from, to),
+        )
+    }
+
+    /// Convert a length between coordinate spaces. The offset is not applied.
+    pub fn convert_length(&self, length: f64, from: CoordSpace, to: CoordSpace) -> f64 {
+        length * self.scale_to(to) / self.scale_to(from)
+    }
+
+    /// Convert extents between coordinate spaces. The offset is not applied.
+    pub fn convert_extents(
+        &self,
+        extents: na::Vector2<f64>,
+        from: CoordSpace,
+        to: CoordSpace,
+    ) -> na::Vector2<f64> {
+        extents * self.scale_to(to) / self.scale_to(from)
+    }
+
     /// Transform Aabb from surface coords to document coords.
     pub fn transform_inv_bounds(&self, bounds: Aabb) -> Aabb {
         bounds.translate(self.offset).scale(1.0 / self.total_zoom())
     }
 
+    /// Transform a point from surface coords to document coords.
+    pub fn screen_to_doc(&self, p: na::Point2<f64>) -> na::Point2<f64> {
+        ((p.coords + self.offset) / self.total_zoom()).into()
+    }
+
+    /// Transform a point from document coords to surface coords.
+    pub fn doc_to_screen(&self, p: na::Point2<f64>) -> na::Point2<f64> {
+        (p.coords * self.total_zoom() - self.offset).into()
+    }
+
     /// The transform from document coords to surface coords.
     ///
     /// To get the inverse, call `.inverse()`.
@@ -371,8 +451,10 @@ impl Camera {
 
 #[cfg(test)]
 mod tests {
+    use super::CoordSpace;
     use crate::Camera;
     use approx::assert_relative_eq;
+    use p2d::bounding_volume::Aabb;
 
     #[test]
     fn transform_vec() {
@@ -408,4 +490,53 @@ mod tests {
         assert_relative_eq!(viewport.mins, mins);
         assert_relative_eq!(viewport.maxs, maxs);
     }
+
+    #[test]
+    fn screen_doc_roundtrip() {
+        let offset = na::vector![4.0, 2.0];
+        let zoom = 1.5;
+        let camera = Camera::default().with_zoom(zoom).with_offset(offset);
+
+        // Point in document coordinates
+        let p0 = na::point![10.0, 2.0];
+        let p0_screen = camera.doc_to_screen(p0);
+
+        assert_relative_eq!(p0_screen, camera.transform().transform_point(&p0));
+        assert_relative_eq!(camera.screen_to_doc(p0_screen), p0);
+    }
+
+    #[test]
+    fn convert_between_spaces() {
+        let offset = na::vector![4.0, 2.0];
+        let zoom = 1.5;
+        let camera = Camera::default().with_zoom(zoom).with_offset(offset);
+
+        let bounds = Aabb::new(na::point![10.0, 2.0], na::point![20.0, 8.0]);
+        let surface_bounds =
+            camera.convert_bounds(bounds, CoordSpace::Document, CoordSpace::Surface);
+
+        assert_relative_eq!(surface_bounds.mins, camera.transform_bounds(bounds).mins);
+        assert_relative_eq!(surface_bounds.maxs, camera.transform_bounds(bounds).maxs);
+        assert_eq!(
+            camera.convert_bounds(bounds, CoordSpace::Document, CoordSpace::Document),
+            bounds
+        );
+        let roundtrip =
+            camera.convert_bounds(surface_bounds, CoordSpace::Surface, CoordSpace::Document);
+        assert_relative_eq!(roundtrip.mins, bounds.mins);
+        assert_relative_eq!(roundtrip.maxs, bounds.maxs);
+
+        assert_relative_eq!(
+            camera.convert_length(3.0, CoordSpace::Surface, CoordSpace::Document),
+            3.0 / zoom
+        );
+        assert_relative_eq!(
+            camera.convert_extents(
+                na::vector![3.0, 6.0],
+                CoordSpace::Document,
+                CoordSpace::Surface
+            ),
+            na::vector![3.0, 6.0] * zoom
+        );
+    }
 }
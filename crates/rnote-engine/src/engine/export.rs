@@ -1,16 +1,25 @@
 // Imports
 use super::{Engine, EngineConfig, StrokeContent};
+use crate::document::format::MeasureUnit;
 use crate::fileformats::rnoteformat::RnoteFile;
 use crate::fileformats::{xoppformat, FileFormatSaver};
+use crate::render::Svg;
+use crate::store::StrokeKey;
+use crate::strokes::{Content, Stroke};
 use crate::CloneConfig;
 use anyhow::Context;
 use futures::channel::oneshot;
+use p2d::bounding_volume::{Aabb, BoundingVolume};
 use rayon::prelude::*;
+use rnote_compose::ext::AabbExt;
+use rnote_compose::shapes::Shapeable;
 use rnote_compose::transform::Transformable;
-use rnote_compose::SplitOrder;
+use rnote_compose::{SplitOrder, Style};
 use serde::{Deserialize, Serialize};
+use slotmap::Key;
 use std::sync::Arc;
-use tracing::error;
+use thiserror::Error;
+use tracing::{error, warn};
 
 /// Document export format.
 #[derive(
@@ -226,6 +235,11 @@ pub enum SelectionExportFormat {
     Png,
     #[serde(rename = "jpeg")]
     Jpeg,
+    /// Lossless WebP, preserving transparency.
+    #[serde(rename = "webp")]
+    WebP,
+    #[serde(rename = "avif")]
+    Avif,
 }
 
 impl Default for SelectionExportFormat {
@@ -240,6 +254,8 @@ impl SelectionExportFormat {
             SelectionExportFormat::Svg => String::from("svg"),
             SelectionExportFormat::Png => String::from("png"),
             SelectionExportFormat::Jpeg => String::from("jpg"),
+            SelectionExportFormat::WebP => String::from("webp"),
+            SelectionExportFormat::Avif => String::from("avif"),
         }
     }
 }
@@ -275,12 +291,40 @@ pub struct SelectionExportPrefs {
     /// The bitmap scale-factor in relation to the actual size.
     #[serde(rename = "bitmap_scalefactor")]
     pub bitmap_scalefactor: f64,
-    /// Quality when exporting as Jpeg.
+    /// Quality when exporting as Jpeg or Avif.
     #[serde(rename = "jpg_quality")]
     pub jpeg_quality: u8,
     /// The margins of the export extending the bounds of the selection.
     #[serde(rename = "margin")]
     pub margin: f64,
+    /// The scale of the Svg width and height attributes in relation to the viewBox, which stays in document units.
+    ///
+    /// Only used when exporting as Svg.
+    #[serde(rename = "unit_scale")]
+    pub unit_scale: f64,
+    /// Whether the export should fail when any of the selected strokes can't be rendered.
+    ///
+    /// Otherwise these strokes are dropped from the export with a warning.
+    #[serde(rename = "strict")]
+    pub strict: bool,
+    /// Whether each stroke should be wrapped in a group with a class and data attribute identifying its type and key.
+    ///
+    /// Only used when exporting as Svg.
+    #[serde(rename = "annotate_svg")]
+    pub annotate_svg: bool,
+    /// Whether the strokes should be grouped into one Inkscape layer per stroke type.
+    ///
+    /// Only used when exporting as Svg.
+    #[serde(rename = "layered_svg")]
+    pub layered_svg: bool,
+    /// The tolerance for simplifying the centerlines of brush strokes, zero disables the simplification.
+    ///
+    /// Only used when exporting as Svg.
+    #[serde(rename = "simplify_tolerance")]
+    pub simplify_tolerance: f64,
+    /// Whether only the selected strokes that are currently drawn should be exported, leaving out hidden strokes.
+    #[serde(rename = "visible_only")]
+    pub visible_only: bool,
 }
 
 impl Default for SelectionExportPrefs {
@@ -293,10 +337,97 @@ impl Default for SelectionExportPrefs {
             bitmap_scalefactor: 1.8,
             jpeg_quality: 85,
             margin: 12.0,
+            unit_scale: 1.0,
+            strict: false,
+            annotate_svg: false,
+            layered_svg: false,
+            simplify_tolerance: 0.0,
+            visible_only: false,
+        }
+    }
+}
+
+/// Provenance metadata that can be embedded into an exported selection Svg.
+#[derive(Debug, Clone, Default)]
+pub struct SvgExportMetadata {
+    /// Emitted as the Svg `<title>` and the Dublin Core title.
+    pub title: Option<String>,
+    /// Emitted in the Svg `<desc>` and as the Dublin Core creator.
+    pub author: Option<String>,
+    /// Emitted in the Svg `<desc>` and as the Dublin Core date.
+    pub creation_date: Option<chrono::DateTime<chrono::Local>>,
+}
+
+impl SvgExportMetadata {
+    /// Generate the `<title>`, `<desc>` and Rdf `<metadata>` elements.
+    ///
+    /// Empty when no metadata is set.
+    fn gen_svg_elements(&self) -> String {
+        if self.title.is_none() && self.author.is_none() && self.creation_date.is_none() {
+            return String::new();
         }
+        let creation_date = self.creation_date.map(|date| date.to_rfc3339());
+        let mut w = xmlwriter::XmlWriter::new(xmlwriter::Options::default());
+
+        if let Some(title) = &self.title {
+            w.start_element("title");
+            w.write_text(title);
+            w.end_element();
+        }
+        let desc = [
+            self.author
+                .as_ref()
+                .map(|author| format!("Author: {author}")),
+            creation_date
+                .as_ref()
+                .map(|date| format!("Created: {date}")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<String>>();
+        if !desc.is_empty() {
+            w.start_element("desc");
+            w.write_text(&desc.join("\n"));
+            w.end_element();
+        }
+
+        w.start_element("metadata");
+        w.start_element("rdf:RDF");
+        w.write_attribute("xmlns:rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#");
+        w.write_attribute("xmlns:dc", "http://purl.org/dc/elements/1.1/");
+        w.start_element("rdf:Description");
+        w.write_attribute("rdf:about", "");
+        for (name, value) in [
+            ("dc:title", self.title.as_ref()),
+            ("dc:creator", self.author.as_ref()),
+            ("dc:date", creation_date.as_ref()),
+        ] {
+            if let Some(value) = value {
+                w.start_element(name);
+                w.write_text(value);
+                w.end_element();
+            }
+        }
+        w.end_document()
     }
 }
 
+/// The G-code command to lift the pen of a plotter.
+const GCODE_PEN_UP: &str = "G0 Z5";
+/// The G-code command to lower the pen of a plotter.
+const GCODE_PEN_DOWN: &str = "G1 Z0";
+
+/// Reasons why the current selection can't be exported.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SelectionExportError {
+    #[error("no strokes are selected")]
+    EmptySelection,
+    #[error("the selection bounds are degenerate")]
+    DegenerateBounds,
+    #[error("the selection contains unsupported content: {0}")]
+    UnsupportedContent(String),
+}
+
 /// Export preferences.
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 #[serde(default, rename = "export_prefs")]
@@ -318,6 +449,17 @@ impl CloneConfig for ExportPrefs {
     }
 }
 
+/// Strokes that are normalized to the origin, so that they can be reused and placed repeatedly, also across documents.
+///
+/// Created with [Engine::selection_to_stamp] and placed with [Engine::place_stamp].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename = "stamp")]
+pub struct Stamp {
+    /// The strokes, with the top-left corner of their bounds at the origin.
+    #[serde(rename = "strokes")]
+    pub strokes: Vec<Stroke>,
+}
+
 impl Engine {
     /// The used image scale-factor for any strokes that are converted to bitmap images on export.
     pub const STROKE_EXPORT_IMAGE_SCALE: f64 = 1.8;
@@ -390,17 +532,35 @@ impl Engine {
     }
 
     pub fn extract_selection_content(&self) -> Option<StrokeContent> {
-        let selection_keys = self.store.selection_keys_as_rendered();
-        if selection_keys.is_empty() {
+        self.extract_content_for_keys(&self.store.selection_keys_as_rendered())
+    }
+
+    fn extract_content_for_keys(&self, keys: &[StrokeKey]) -> Option<StrokeContent> {
+        if keys.is_empty() {
             return None;
         }
         Some(
             StrokeContent::default()
-                .with_strokes(self.store.get_strokes_arc(&selection_keys))
+                .with_strokes(self.store.get_strokes_arc(keys))
                 .with_background(Some(self.document.background)),
         )
     }
 
+    /// The keys of the selected strokes that should be exported, in the order they are rendered.
+    fn selection_export_keys(
+        &self,
+        selection_export_prefs: &SelectionExportPrefs,
+    ) -> Vec<StrokeKey> {
+        let selection_keys = self.store.selection_keys_as_rendered();
+        if !selection_export_prefs.visible_only {
+            return selection_keys;
+        }
+        selection_keys
+            .into_iter()
+            .filter(|&key| self.store.rendered(key))
+            .collect()
+    }
+
     /// Export the current engine config as Json string.
     pub fn export_engine_config_as_json(&self) -> anyhow::Result<String> {
         Ok(serde_json::to_string(&self.extract_engine_config())?)
@@ -413,6 +573,32 @@ impl Engine {
         Ok(serde_json::to_string_pretty(self)?)
     }
 
+    /// Export the selected strokes as a human-readable Json array, in the order they are rendered.
+    ///
+    /// Meant for scripting and external tooling, the strokes can be inserted again with [Engine::insert_from_json].
+    pub fn selection_to_json(&self) -> anyhow::Result<String> {
+        let strokes = self
+            .store
+            .get_strokes_arc(&self.store.selection_keys_as_rendered());
+        Ok(serde_json::to_string_pretty(&strokes)?)
+    }
+
+    /// Convert the selected strokes into a stamp, moving the top-left corner of their bounds to the origin.
+    ///
+    /// Errors if nothing is selected.
+    pub fn selection_to_stamp(&self) -> anyhow::Result<Stamp> {
+        let selection_keys = self.store.selection_keys_as_rendered();
+        let selection_bounds = self
+            .store
+            .bounds_for_strokes(&selection_keys)
+            .ok_or_else(|| anyhow::anyhow!("Creating stamp failed, nothing is selected."))?;
+        let mut strokes = self.store.clone_strokes(&selection_keys);
+        strokes
+            .iter_mut()
+            .for_each(|stroke| stroke.translate(-selection_bounds.mins.coords));
+        Ok(Stamp { strokes })
+    }
+
     /// Export the document.
     pub fn export_doc(
         &self,
@@ -794,6 +980,40 @@ impl Engine {
         oneshot_receiver
     }
 
+    /// Validate that the current selection can be exported, without generating the export.
+    ///
+    /// Can be used to check whether exporting is possible before any data gets written.
+    pub fn validate_selection_export(&self) -> Result<(), SelectionExportError> {
+        let selection_keys = self.store.selection_keys_as_rendered();
+        if selection_keys.is_empty() {
+            return Err(SelectionExportError::EmptySelection);
+        }
+        // Checked before the bounds, because the bounds of unsupported content can't be computed
+        for stroke in self.store.get_strokes_ref(&selection_keys) {
+            if let Stroke::BrushStroke(brushstroke) = stroke {
+                if matches!(brushstroke.style, Style::Rough(_)) {
+                    return Err(SelectionExportError::UnsupportedContent(String::from(
+                        "brush strokes with rough style",
+                    )));
+                }
+            }
+        }
+        let Some(bounds) = self.store.bounds_for_strokes(&selection_keys) else {
+            return Err(SelectionExportError::EmptySelection);
+        };
+        let extents = bounds
+            .loosened(self.export_prefs.selection_export_prefs.margin)
+            .extents();
+        if !extents[0].is_finite()
+            || !extents[1].is_finite()
+            || extents[0] <= 0.0
+            || extents[1] <= 0.0
+        {
+            return Err(SelectionExportError::DegenerateBounds);
+        }
+        Ok(())
+    }
+
     /// Exports the current selection.
     pub fn export_selection(
         &self,
@@ -804,55 +1024,120 @@ impl Engine {
 
         match selection_export_prefs.export_format {
             SelectionExportFormat::Svg => {
-                self.export_selection_as_svg_bytes(selection_export_prefs_override)
+                self.export_selection_as_svg_bytes(selection_export_prefs_override, None)
             }
-            SelectionExportFormat::Png | SelectionExportFormat::Jpeg => {
+            SelectionExportFormat::Png
+            | SelectionExportFormat::Jpeg
+            | SelectionExportFormat::WebP
+            | SelectionExportFormat::Avif => {
                 self.export_selection_as_bitmap_bytes(selection_export_prefs_override)
             }
         }
     }
 
+    /// Exports the selection as Svg with the given provenance metadata embedded,
+    /// regardless of the configured export format.
+    pub fn export_selection_as_svg_with_metadata(
+        &self,
+        selection_export_prefs_override: Option<SelectionExportPrefs>,
+        metadata: SvgExportMetadata,
+    ) -> oneshot::Receiver<Result<Option<Vec<u8>>, anyhow::Error>> {
+        self.export_selection_as_svg_bytes(selection_export_prefs_override, Some(metadata))
+    }
+
     /// Exports the selection as Svg.
     fn export_selection_as_svg_bytes(
         &self,
         selection_export_prefs_override: Option<SelectionExportPrefs>,
+        metadata: Option<SvgExportMetadata>,
     ) -> oneshot::Receiver<Result<Option<Vec<u8>>, anyhow::Error>> {
         let (oneshot_sender, oneshot_receiver) =
             oneshot::channel::<anyhow::Result<Option<Vec<u8>>>>();
         let selection_export_prefs =
             selection_export_prefs_override.unwrap_or(self.export_prefs.selection_export_prefs);
-        let content = self.extract_selection_content();
+        let selection_keys = self.selection_export_keys(&selection_export_prefs);
+        let content = self.extract_content_for_keys(&selection_keys);
 
         rayon::spawn(move || {
             let result = || -> Result<Option<Vec<u8>>, anyhow::Error> {
                 let Some(content) = content else {
                     return Ok(None);
                 };
-                let Some(svg) = content.gen_svg(
-                    selection_export_prefs.with_background,
-                    selection_export_prefs.with_pattern,
-                    selection_export_prefs.optimize_printing,
-                    selection_export_prefs.margin,
-                )?
-                else {
-                    return Ok(None);
-                };
+                gen_selection_svg_bytes(
+                    content,
+                    &selection_keys,
+                    &selection_export_prefs,
+                    metadata.as_ref(),
+                    None,
+                )
+            };
+            if oneshot_sender.send(result()).is_err() {
+                error!("Sending result to receiver failed while exporting selection as Svg bytes. Receiver already dropped.");
+            }
+        });
 
-                Ok(Some(
-                    rnote_compose::utils::add_xml_header(
-                        rnote_compose::utils::wrap_svg_root(
-                            svg.svg_data.as_str(),
-                            Some(svg.bounds),
-                            Some(svg.bounds),
-                            false,
+        oneshot_receiver
+    }
+
+    /// Export each selected stroke as a separate Svg, each with its own tight bounds.
+    ///
+    /// The Svgs are in the order that the strokes are rendered.
+    pub fn export_selection_strokes_as_svgs_bytes(
+        &self,
+        selection_export_prefs_override: Option<SelectionExportPrefs>,
+    ) -> oneshot::Receiver<Result<Vec<Vec<u8>>, anyhow::Error>> {
+        let (oneshot_sender, oneshot_receiver) = oneshot::channel::<anyhow::Result<Vec<Vec<u8>>>>();
+        let selection_export_prefs =
+            selection_export_prefs_override.unwrap_or(self.export_prefs.selection_export_prefs);
+        let strokes_content = self
+            .store
+            .get_strokes_arc(&self.selection_export_keys(&selection_export_prefs))
+            .into_iter()
+            .map(|stroke| {
+                StrokeContent::default()
+                    .with_strokes(vec![stroke])
+                    .with_background(Some(self.document.background))
+            })
+            .collect::<Vec<StrokeContent>>();
+
+        rayon::spawn(move || {
+            let result = || -> anyhow::Result<Vec<Vec<u8>>> {
+                strokes_content
+                    .into_par_iter()
+                    .enumerate()
+                    .map(|(i, mut stroke_content)| {
+                        simplify_brushstrokes(
+                            &mut stroke_content,
+                            selection_export_prefs.simplify_tolerance,
+                        );
+                        let svg = stroke_content
+                            .gen_svg(
+                                selection_export_prefs.with_background,
+                                selection_export_prefs.with_pattern,
+                                selection_export_prefs.optimize_printing,
+                                selection_export_prefs.margin,
+                            )?
+                            .ok_or(anyhow::anyhow!(
+                                "Generating Svg for selected stroke {i} failed, returned None."
+                            ))?;
+                        Ok(rnote_compose::utils::add_xml_header(
+                            rnote_compose::utils::wrap_svg_root(
+                                svg.svg_data.as_str(),
+                                Some(svg.bounds.scale(selection_export_prefs.unit_scale)),
+                                Some(svg.bounds),
+                                false,
+                            )
+                            .as_str(),
                         )
-                        .as_str(),
-                    )
-                    .into_bytes(),
-                ))
+                        .into_bytes())
+                    })
+                    .collect()
             };
+
             if oneshot_sender.send(result()).is_err() {
-                error!("Sending result to receiver failed while exporting selection as Svg bytes. Receiver already dropped.");
+                error!(
+                    "Sending result to receiver failed while exporting selected strokes as Svg bytes. Receiver already dropped."
+                );
             }
         });
 
@@ -870,26 +1155,30 @@ impl Engine {
             oneshot::channel::<anyhow::Result<Option<Vec<u8>>>>();
         let selection_export_prefs =
             selection_export_prefs_override.unwrap_or(self.export_prefs.selection_export_prefs);
-        let content = self.extract_selection_content();
+        let selection_keys = self.selection_export_keys(&selection_export_prefs);
+        let content = self.extract_content_for_keys(&selection_keys);
 
         rayon::spawn(move || {
             let result = || -> Result<Option<Vec<u8>>, anyhow::Error> {
                 let Some(content) = content else {
                     return Ok(None);
                 };
-                let Some(svg) = content.gen_svg(
-                    selection_export_prefs.with_background,
-                    selection_export_prefs.with_pattern,
+                let rendered = render_selection_strokes(
+                    &content,
+                    &selection_keys,
+                    selection_export_prefs.strict,
                     selection_export_prefs.optimize_printing,
-                    selection_export_prefs.margin,
-                )?
+                )?;
+                let Some(svg) = gen_selection_svg(&content, rendered, &selection_export_prefs)?
                 else {
                     return Ok(None);
                 };
                 let image_format = match selection_export_prefs.export_format {
                     SelectionExportFormat::Svg => return Err(anyhow::anyhow!("Extracting bitmap image format from doc pages export prefs failed, not set to a bitmap format.")),
                     SelectionExportFormat::Png => image::ImageFormat::Png,
-                    SelectionExportFormat::Jpeg => image::ImageFormat::Jpeg
+                    SelectionExportFormat::Jpeg => image::ImageFormat::Jpeg,
+                    SelectionExportFormat::WebP => image::ImageFormat::WebP,
+                    SelectionExportFormat::Avif => image::ImageFormat::Avif,
                 };
 
                 Ok(Some(
@@ -907,4 +1196,589 @@ impl Engine {
 
         oneshot_receiver
     }
+
+    /// Render the selection into a cairo image surface that fits within `max_size` pixels on either side,
+    /// for example for thumbnails. The background and margin are taken from the selection export preferences.
+    ///
+    /// Errors if nothing is selected.
+    pub fn render_selection_to_surface(
+        &self,
+        max_size: u32,
+    ) -> anyhow::Result<cairo::ImageSurface> {
+        let selection_export_prefs = &self.export_prefs.selection_export_prefs;
+        let content = self
+            .extract_content_for_keys(&self.store.selection_keys_as_rendered())
+            .ok_or_else(|| {
+                anyhow::anyhow!("Rendering selection to surface failed, nothing is selected.")
+            })?;
+        let bounds = content
+            .bounds()
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Rendering selection to surface failed, the selection has no bounds."
+                )
+            })?
+            .loosened(selection_export_prefs.margin);
+        let extents = bounds.extents();
+        let image_scale = f64::from(max_size) / extents.max();
+        if !image_scale.is_finite() || image_scale <= 0.0 {
+            return Err(anyhow::anyhow!(
+                "Rendering selection to surface failed, invalid image scale {image_scale} for max size {max_size}."
+            ));
+        }
+        let width_scaled = ((extents[0] * image_scale).round() as i32).max(1);
+        let height_scaled = ((extents[1] * image_scale).round() as i32).max(1);
+
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, width_scaled, height_scaled)
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Creating ImageSurface with dimensions ({width_scaled}, {height_scaled}) failed, Err: {e:?}"
+                )
+            })?;
+        // Context in new scope, so that it is dropped before the surface is returned
+        {
+            let cairo_cx = cairo::Context::new(&surface)?;
+            cairo_cx.scale(image_scale, image_scale);
+            cairo_cx.translate(-bounds.mins[0], -bounds.mins[1]);
+            content.draw_to_cairo(
+                &cairo_cx,
+                selection_export_prefs.with_background,
+                selection_export_prefs.with_pattern,
+                selection_export_prefs.optimize_printing,
+                selection_export_prefs.margin,
+                image_scale,
+            )?;
+        }
+        surface.flush();
+        Ok(surface)
+    }
+
+    /// Generate the selection Svg as a `data:image/svg+xml;base64,..` URI, for embedding in web content or markdown.
+    ///
+    /// Returns None if nothing is selected.
+    pub fn gen_selection_data_uri(&self) -> anyhow::Result<Option<String>> {
+        let Some(content) = self.extract_selection_content() else {
+            return Ok(None);
+        };
+        let Some(svg_bytes) = gen_selection_svg_bytes(
+            content,
+            &self.store.selection_keys_as_rendered(),
+            &self.export_prefs.selection_export_prefs,
+            None,
+            None,
+        )?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(format!(
+            "data:image/svg+xml;base64,{}",
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, svg_bytes)
+        )))
+    }
+
+    /// Generate the selection Svg with the selection export preferences.
+    ///
+    /// When a content rectangle is given, the export is clipped to it, e.g. to keep scratch notes in the page margins
+    /// out of a shared export. The document is not modified. Returns None if nothing is selected
+    /// or the selection lies entirely outside of the content rectangle.
+    pub fn gen_svg_selection(
+        &self,
+        clip_to_margins: Option<Aabb>,
+    ) -> anyhow::Result<Option<String>> {
+        let Some(content) = self.extract_selection_content() else {
+            return Ok(None);
+        };
+        let Some(svg_bytes) = gen_selection_svg_bytes(
+            content,
+            &self.store.selection_keys_as_rendered(),
+            &self.export_prefs.selection_export_prefs,
+            None,
+            clip_to_margins,
+        )?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(String::from_utf8(svg_bytes)?))
+    }
+
+    /// Generate a Svg of the selected strokes, clipped by the outline of the shape stroke with the given key.
+    ///
+    /// The mask shape itself is not drawn and the background is left out.
+    /// Errors if the mask is not a shape stroke. Returns None if no other strokes are selected.
+    pub fn gen_svg_selection_clipped_by(
+        &self,
+        mask_key: StrokeKey,
+    ) -> anyhow::Result<Option<String>> {
+        let Some(Stroke::ShapeStroke(mask)) = self.store.get_stroke_ref(mask_key) else {
+            return Err(anyhow::anyhow!(
+                "Generating the clipped selection Svg failed, the mask is not a shape stroke."
+            ));
+        };
+        let keys = self
+            .store
+            .selection_keys_as_rendered()
+            .into_iter()
+            .filter(|&key| key != mask_key)
+            .collect::<Vec<StrokeKey>>();
+        if keys.is_empty() {
+            return Ok(None);
+        }
+        let margin = self.export_prefs.selection_export_prefs.margin;
+        let bounds = mask.bounds().loosened(margin);
+        let clip_id = format!("rnote-clip-{}", mask_key.data().as_ffi());
+
+        let strokes_svg_data = self
+            .store
+            .get_strokes_arc(&keys)
+            .into_iter()
+            .map(|stroke| Ok(stroke.gen_svg()?.svg_data))
+            .collect::<anyhow::Result<String>>()?;
+        let clip_path = svg::node::element::ClipPath::new()
+            .set("id", clip_id.as_str())
+            .add(svg::node::element::Path::new().set("d", mask.outline_path().to_svg()));
+        let clipped = svg::node::element::Group::new()
+            .set("clip-path", format!("url(#{clip_id})"))
+            .add(svg::node::Blob::new(strokes_svg_data));
+        let svg_data = rnote_compose::utils::svg_node_to_string(
+            &svg::node::element::Definitions::new().add(clip_path),
+        )? + rnote_compose::utils::svg_node_to_string(&clipped)?.as_str();
+
+        Ok(Some(rnote_compose::utils::add_xml_header(
+            rnote_compose::utils::wrap_svg_root(
+                svg_data.as_str(),
+                Some(bounds.scale(self.export_prefs.selection_export_prefs.unit_scale)),
+                Some(bounds),
+                false,
+            )
+            .as_str(),
+        )))
+    }
+
+    /// Generate an Svg of the selection that reveals the selected strokes one after another in chronological order,
+    /// using SMIL animations.
+    ///
+    /// Every stroke fades in during an equal share of the total duration. The background is left out.
+    /// Returns None if nothing is selected.
+    pub fn gen_svg_selection_animated(
+        &self,
+        total_duration_ms: u32,
+    ) -> anyhow::Result<Option<String>> {
+        let keys = self.store.selection_keys_sorted_chrono_t();
+        let Some(selection_bounds) = self.store.bounds_for_strokes(&keys) else {
+            return Ok(None);
+        };
+        let bounds = selection_bounds.loosened(self.export_prefs.selection_export_prefs.margin);
+        let stroke_duration_ms = (total_duration_ms / keys.len() as u32).max(1);
+
+        let svg_data = self
+            .store
+            .get_strokes_arc(&keys)
+            .into_iter()
+            .enumerate()
+            .map(|(rank, stroke)| {
+                let reveal = svg::node::element::Animate::new()
+                    .set("attributeName", "opacity")
+                    .set("from", 0)
+                    .set("to", 1)
+                    .set("begin", format!("{}ms", rank as u32 * stroke_duration_ms))
+                    .set("dur", format!("{stroke_duration_ms}ms"))
+                    .set("fill", "freeze");
+                let group = svg::node::element::Group::new()
+                    .set("opacity", 0)
+                    .add(reveal)
+                    .add(svg::node::Blob::new(stroke.gen_svg()?.svg_data));
+                rnote_compose::utils::svg_node_to_string(&group)
+            })
+            .collect::<anyhow::Result<String>>()?;
+
+        Ok(Some(rnote_compose::utils::add_xml_header(
+            rnote_compose::utils::wrap_svg_root(
+                svg_data.as_str(),
+                Some(bounds.scale(self.export_prefs.selection_export_prefs.unit_scale)),
+                Some(bounds),
+                false,
+            )
+            .as_str(),
+        )))
+    }
+
+    /// Generate G-code for pen plotters from the centerlines of the selected strokes.
+    ///
+    /// Brush strokes contribute their path elements, shape strokes their flattened outlines.
+    /// Other strokes are skipped with a warning.
+    /// The coordinates are in millimeters, with the origin at the bottom left corner of the selection bounds.
+    ///
+    /// Returns None if nothing is selected.
+    pub fn gen_gcode_selection(&self, feed_rate: f64) -> anyhow::Result<Option<String>> {
+        use std::fmt::Write;
+        /// The tolerance when flattening shape outlines, in document coordinates.
+        const FLATTEN_TOLERANCE: f64 = 0.1;
+
+        if !feed_rate.is_finite() || feed_rate <= 0.0 {
+            return Err(anyhow::anyhow!(
+                "Generating G-code for selection failed, invalid feed rate {feed_rate}."
+            ));
+        }
+        let selection_keys = self.store.selection_keys_as_rendered();
+        let Some(selection_bounds) = self.store.bounds_for_strokes(&selection_keys) else {
+            return Ok(None);
+        };
+        let dpi = self.document.format.dpi();
+        let to_mm = |pos: na::Vector2<f64>| {
+            na::vector![
+                MeasureUnit::convert_measurement(
+                    pos[0] - selection_bounds.mins[0],
+                    MeasureUnit::Px,
+                    dpi,
+                    MeasureUnit::Mm,
+                    dpi
+                ),
+                // plotters have their y-axis pointing upwards
+                MeasureUnit::convert_measurement(
+                    selection_bounds.maxs[1] - pos[1],
+                    MeasureUnit::Px,
+                    dpi,
+                    MeasureUnit::Mm,
+                    dpi
+                )
+            ]
+        };
+
+        let mut polylines: Vec<Vec<na::Vector2<f64>>> = vec![];
+        for stroke in self.store.get_strokes_arc(&selection_keys) {
+            match stroke.as_ref() {
+                Stroke::BrushStroke(brushstroke) => polylines.push(
+                    std::iter::once(brushstroke.path.start.pos)
+                        .chain(brushstroke.path.segments.iter().map(|seg| seg.end().pos))
+                        .collect(),
+                ),
+                Stroke::ShapeStroke(shapestroke) => {
+                    let mut polyline = vec![];
+                    kurbo::flatten(shapestroke.shape.outline_path(), FLATTEN_TOLERANCE, |el| {
+                        match el {
+                            kurbo::PathEl::MoveTo(p) => {
+                                polylines.push(std::mem::take(&mut polyline));
+                                polyline.push(na::vector![p.x, p.y]);
+                            }
+                            kurbo::PathEl::LineTo(p) => polyline.push(na::vector![p.x, p.y]),
+                            kurbo::PathEl::ClosePath => {
+                                if let Some(first) = polyline.first().copied() {
+                                    polyline.push(first);
+                                }
+                            }
+                            // flatten only emits MoveTo, LineTo and ClosePath
+                            _ => {}
+                        }
+                    });
+                    polylines.push(polyline);
+                }
+                _ => {
+                    warn!("Skipping stroke that is not a brush or shape stroke while generating G-code for selection.");
+                }
+            }
+        }
+
+        let mut gcode = String::new();
+        writeln!(gcode, "G21")?;
+        writeln!(gcode, "G90")?;
+        writeln!(gcode, "{GCODE_PEN_UP}")?;
+        for polyline in polylines {
+            let mut points = polyline.into_iter().map(to_mm);
+            let Some(start) = points.next() else {
+                continue;
+            };
+            writeln!(gcode, "G0 X{:.3} Y{:.3}", start[0], start[1])?;
+            writeln!(gcode, "{GCODE_PEN_DOWN}")?;
+            for point in points {
+                writeln!(
+                    gcode,
+                    "G1 X{:.3} Y{:.3} F{feed_rate:.1}",
+                    point[0], point[1]
+                )?;
+            }
+            writeln!(gcode, "{GCODE_PEN_UP}")?;
+        }
+        writeln!(gcode, "G0 X0 Y0")?;
+
+        Ok(Some(gcode))
+    }
+}
+
+/// Render the Svg of every stroke of the selection content once, dropping the strokes that fail to render
+/// and logging their keys.
+///
+/// `keys` must be the selection keys in the order of the content strokes.
+/// When `strict` is set, an error is returned instead.
+/// When optimizing for printing, the strokes that are not inside an image are rendered in their darkest color,
+/// like [StrokeContent::draw_to_cairo] does.
+///
+/// Returns the keys and strokes of the rendered strokes together with their Svgs,
+/// so that the export is assembled from them without rendering the strokes again.
+fn render_selection_strokes<'a>(
+    content: &'a StrokeContent,
+    keys: &[StrokeKey],
+    strict: bool,
+    optimize_printing: bool,
+) -> anyhow::Result<Vec<(StrokeKey, &'a Stroke, Svg)>> {
+    let image_bounds = content
+        .strokes
+        .iter()
+        .filter_map(|stroke| match stroke.as_ref() {
+            Stroke::BitmapImage(image) => Some(image.rectangle.bounds()),
+            Stroke::VectorImage(image) => Some(image.rectangle.bounds()),
+            _ => None,
+        })
+        .collect::<Vec<Aabb>>();
+    let mut rendered = Vec::with_capacity(keys.len());
+    let mut failed = vec![];
+    for (stroke, &key) in content.strokes.iter().zip(keys) {
+        let stroke_bounds = stroke.bounds();
+        let svg = if optimize_printing
+            && image_bounds
+                .iter()
+                .all(|bounds| !bounds.contains(&stroke_bounds))
+        {
+            let mut darkest_color_stroke = stroke.as_ref().clone();
+            darkest_color_stroke.set_to_darkest_color();
+            darkest_color_stroke.gen_svg()
+        } else {
+            stroke.gen_svg()
+        };
+        match svg {
+            Ok(svg) => rendered.push((key, stroke.as_ref(), svg)),
+            Err(e) => failed.push((key, e)),
+        }
+    }
+    if strict {
+        if let Some((key, e)) = failed.first() {
+            return Err(anyhow::anyhow!(
+                "Rendering {} of the selected strokes failed, first failed stroke with key {key:?}, Err: {e:?}",
+                failed.len()
+            ));
+        }
+    }
+    for (key, e) in failed {
+        warn!("Dropping stroke with key {key:?} from the selection export, rendering failed, Err: {e:?}");
+    }
+    Ok(rendered)
+}
+
+/// The labels of the layers in a layered selection Svg, from bottom to top.
+const SELECTION_SVG_LAYER_LABELS: [&str; 4] = ["Images", "Shapes", "Handwriting", "Text"];
+
+/// The index into [SELECTION_SVG_LAYER_LABELS] of the layer the stroke is exported to.
+fn selection_svg_layer_index(stroke: &Stroke) -> usize {
+    match stroke {
+        Stroke::VectorImage(_) | Stroke::BitmapImage(_) => 0,
+        Stroke::ShapeStroke(_) => 1,
+        Stroke::BrushStroke(_) => 2,
+        Stroke::TextStroke(_) => 3,
+    }
+}
+
+/// Generate the selection Svg from the rendered strokes of the content.
+///
+/// When annotating, every stroke is wrapped in a group annotated with its stroke type and key.
+/// When layered, the strokes are sorted into one Inkscape layer per stroke type.
+///
+/// Without groups the Svg is simplified like [StrokeContent::gen_svg] does,
+/// with groups it is not, because that would strip them.
+fn gen_selection_svg(
+    content: &StrokeContent,
+    rendered: Vec<(StrokeKey, &Stroke, Svg)>,
+    selection_export_prefs: &SelectionExportPrefs,
+) -> anyhow::Result<Option<Svg>> {
+    let Some(bounds_loosened) = content
+        .bounds()
+        .map(|b| b.loosened(selection_export_prefs.margin))
+    else {
+        return Ok(None);
+    };
+    let mut selection_svg = Svg {
+        svg_data: String::new(),
+        bounds: bounds_loosened,
+    };
+    let mut layers: [Vec<Svg>; SELECTION_SVG_LAYER_LABELS.len()] = Default::default();
+
+    if selection_export_prefs.with_background {
+        if let Some(background) = &content.background {
+            selection_svg.merge([background.gen_svg(
+                bounds_loosened,
+                selection_export_prefs.with_pattern,
+                selection_export_prefs.optimize_printing,
+            )?]);
+        }
+    }
+
+    for (key, stroke, mut stroke_svg) in rendered {
+        if selection_export_prefs.annotate_svg {
+            let stroke_type = match stroke {
+                Stroke::BrushStroke(_) => "brushstroke",
+                Stroke::ShapeStroke(_) => "shapestroke",
+                Stroke::TextStroke(_) => "textstroke",
+                Stroke::VectorImage(_) => "vectorimage",
+                Stroke::BitmapImage(_) => "bitmapimage",
+            };
+            let group = svg::node::element::Group::new()
+                .set("class", format!("rnote-stroke rnote-{stroke_type}"))
+                .set("data-rnote-key", key.data().as_ffi().to_string())
+                .add(svg::node::Blob::new(stroke_svg.svg_data));
+            stroke_svg.svg_data = rnote_compose::utils::svg_node_to_string(&group)?;
+        }
+
+        if selection_export_prefs.layered_svg {
+            layers[selection_svg_layer_index(stroke)].push(stroke_svg);
+        } else {
+            selection_svg.merge([stroke_svg]);
+        }
+    }
+
+    for (label, layer_svgs) in SELECTION_SVG_LAYER_LABELS.into_iter().zip(layers) {
+        let Some(layer_bounds) = layer_svgs
+            .iter()
+            .map(|svg| svg.bounds)
+            .reduce(|acc, bounds| acc.merged(&bounds))
+        else {
+            continue;
+        };
+        let layer = svg::node::element::Group::new()
+            .set(
+                "xmlns:inkscape",
+                "http://www.inkscape.org/namespaces/inkscape",
+            )
+            .set("inkscape:groupmode", "layer")
+            .set("inkscape:label", label)
+            .add(svg::node::Blob::new(
+                layer_svgs
+                    .into_iter()
+                    .map(|svg| svg.svg_data)
+                    .collect::<String>(),
+            ));
+
+        selection_svg.merge([Svg {
+            svg_data: rnote_compose::utils::svg_node_to_string(&layer)?,
+            bounds: layer_bounds,
+        }]);
+    }
+
+    if !(selection_export_prefs.annotate_svg || selection_export_prefs.layered_svg) {
+        // The simplification also moves the bounds to mins: [0.0, 0.0], maxs: extents
+        if let Err(e) = selection_svg.simplify() {
+            warn!("Simplifying Svg while generating selection Svg failed, Err: {e:?}");
+        }
+    }
+
+    Ok(Some(selection_svg))
+}
+
+/// Simplify the centerlines of the brush strokes in the content with the given tolerance.
+///
+/// A tolerance of zero leaves the content untouched.
+fn simplify_brushstrokes(content: &mut StrokeContent, tolerance: f64) {
+    if tolerance <= 0.0 {
+        return;
+    }
+    for stroke in content.strokes.iter_mut() {
+        if let Stroke::BrushStroke(brushstroke) = stroke.as_ref() {
+            let mut brushstroke = brushstroke.clone();
+            brushstroke.replace_path(brushstroke.path.simplified(tolerance));
+            *stroke = Arc::new(Stroke::BrushStroke(brushstroke));
+        }
+    }
+}
+
+/// Generate the Svg bytes of the selection content, including the Xml header.
+///
+/// The metadata is embedded at the start of the Svg root, if given.
+/// When a clip rectangle is given, the content is clipped to it and the Svg bounds are shrunk to it.
+fn gen_selection_svg_bytes(
+    mut content: StrokeContent,
+    selection_keys: &[StrokeKey],
+    selection_export_prefs: &SelectionExportPrefs,
+    metadata: Option<&SvgExportMetadata>,
+    clip_to_margins: Option<Aabb>,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    simplify_brushstrokes(&mut content, selection_export_prefs.simplify_tolerance);
+    let rendered = render_selection_strokes(
+        &content,
+        selection_keys,
+        selection_export_prefs.strict,
+        selection_export_prefs.optimize_printing,
+    )?;
+    let Some(mut svg) = gen_selection_svg(&content, rendered, selection_export_prefs)? else {
+        return Ok(None);
+    };
+    if let Some(clip) = clip_to_margins {
+        let Some(clipped_bounds) = svg.bounds.intersection(&clip) else {
+            return Ok(None);
+        };
+        let clip_id = "rnote-margin-clip";
+        let clip_path = svg::node::element::ClipPath::new().set("id", clip_id).add(
+            svg::node::element::Rectangle::new()
+                .set("x", clip.mins[0])
+                .set("y", clip.mins[1])
+                .set("width", clip.extents()[0])
+                .set("height", clip.extents()[1]),
+        );
+        let clipped = svg::node::element::Group::new()
+            .set("clip-path", format!("url(#{clip_id})"))
+            .add(svg::node::Blob::new(svg.svg_data));
+        svg.svg_data = rnote_compose::utils::svg_node_to_string(
+            &svg::node::element::Definitions::new().add(clip_path),
+        )? + rnote_compose::utils::svg_node_to_string(&clipped)?.as_str();
+        svg.bounds = clipped_bounds;
+    }
+    if let Some(metadata) = metadata {
+        svg.svg_data.insert_str(0, &metadata.gen_svg_elements());
+    }
+
+    Ok(Some(
+        rnote_compose::utils::add_xml_header(
+            rnote_compose::utils::wrap_svg_root(
+                svg.svg_data.as_str(),
+                Some(svg.bounds.scale(selection_export_prefs.unit_scale)),
+                Some(svg.bounds),
+                false,
+            )
+            .as_str(),
+        )
+        .into_bytes(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SelectionExportError;
+    use crate::strokes::{BrushStroke, Stroke};
+    use crate::Engine;
+    use rnote_compose::penpath::Element;
+    use rnote_compose::style::rough::RoughOptions;
+    use rnote_compose::Style;
+
+    #[test]
+    fn validate_selection_export_rejects_rough_brushstroke() {
+        let mut engine = Engine::default();
+        assert_eq!(
+            engine.validate_selection_export(),
+            Err(SelectionExportError::EmptySelection)
+        );
+
+        let key = engine.store.insert_stroke(
+            Stroke::BrushStroke(BrushStroke::new(
+                Element::new(na::vector![0.0, 0.0], 1.0),
+                Style::default(),
+            )),
+            None,
+        );
+        // Rough brush strokes can't compute their bounds, so the style is swapped after inserting
+        if let Some(Stroke::BrushStroke(brushstroke)) = engine.store.get_stroke_mut(key) {
+            brushstroke.style = Style::Rough(RoughOptions::default());
+        }
+        engine.store.set_selected(key, true);
+
+        assert!(matches!(
+            engine.validate_selection_export(),
+            Err(SelectionExportError::UnsupportedContent(_))
+        ));
+    }
 }
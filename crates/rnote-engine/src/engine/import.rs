@@ -1,16 +1,17 @@
 // Imports
-use super::{EngineConfig, EngineViewMut, StrokeContent};
+use super::{EngineConfig, EngineViewMut, Stamp, StrokeContent};
 use crate::document::Layout;
 use crate::pens::Pen;
 use crate::pens::PenStyle;
 use crate::store::chrono_comp::StrokeLayer;
-use crate::store::StrokeKey;
+use crate::store::{StrokeKey, StrokeStore};
 use crate::strokes::{resize::calculate_resize_ratio, resize::ImageSizeOption, Resize};
 use crate::strokes::{BitmapImage, Stroke, VectorImage};
 use crate::{CloneConfig, Engine, WidgetFlags};
 use futures::channel::oneshot;
 use rnote_compose::ext::Vector2Ext;
 use rnote_compose::shapes::Shapeable;
+use rnote_compose::transform::Transformable;
 use serde::{Deserialize, Serialize};
 use std::ops::Range;
 use std::path::PathBuf;
@@ -498,4 +499,152 @@ impl Engine {
 
         widget_flags
     }
+
+    /// Insert strokes from a Json array, as generated by [Engine::selection_to_json].
+    ///
+    /// The inserted strokes become the selection. Returns the keys of the inserted strokes.
+    pub fn insert_from_json(
+        &mut self,
+        json: &str,
+    ) -> anyhow::Result<(Vec<StrokeKey>, WidgetFlags)> {
+        let strokes: Vec<Stroke> = serde_json::from_str(json)?;
+        Ok(self.insert_strokes_as_selection(strokes))
+    }
+
+    /// Place a copy of the stamp with the top-left corner of its bounds at the given position.
+    ///
+    /// The placed strokes become the selection. Returns the keys of the placed strokes.
+    pub fn place_stamp(
+        &mut self,
+        stamp: &Stamp,
+        pos: na::Vector2<f64>,
+    ) -> (Vec<StrokeKey>, WidgetFlags) {
+        let strokes = stamp
+            .strokes
+            .iter()
+            .cloned()
+            .map(|mut stroke| {
+                stroke.translate(pos);
+                stroke
+            })
+            .collect();
+        self.insert_strokes_as_selection(strokes)
+    }
+
+    /// Append all strokes of another document, offset by the given vector, for example to merge two documents.
+    ///
+    /// The appended strokes become the selection, so they can be repositioned right away.
+    /// Returns the keys of the appended strokes.
+    pub fn append_strokes_from(
+        &mut self,
+        other: &StrokeStore,
+        offset: na::Vector2<f64>,
+    ) -> (Vec<StrokeKey>, WidgetFlags) {
+        let mut widget_flags = self.change_pen_style(PenStyle::Selector);
+        let appended = self.store.append_strokes_from(other, offset);
+        if appended.is_empty() {
+            return (appended, widget_flags);
+        }
+        self.store.update_geometry_for_strokes(&appended);
+        self.store.regenerate_rendering_for_strokes_threaded(
+            self.tasks_tx.clone(),
+            &appended,
+            self.camera.viewport(),
+            self.camera.image_scale(),
+        );
+
+        widget_flags |= self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport();
+        (appended, widget_flags)
+    }
+
+    /// Insert the strokes and make them the selection, deselecting all other strokes.
+    fn insert_strokes_as_selection(
+        &mut self,
+        strokes: Vec<Stroke>,
+    ) -> (Vec<StrokeKey>, WidgetFlags) {
+        let mut widget_flags = WidgetFlags::default();
+        if strokes.is_empty() {
+            return (vec![], widget_flags);
+        }
+
+        // we need to always deselect all strokes
+        // even though changing the pen style deselects too, but only when the pen is actually different.
+        let all_strokes = self.store.stroke_keys_as_rendered();
+        self.store.set_selected_keys(&all_strokes, false);
+        widget_flags |= self.change_pen_style(PenStyle::Selector);
+
+        let inserted = strokes
+            .into_iter()
+            .map(|stroke| self.store.insert_stroke(stroke, None))
+            .collect::<Vec<StrokeKey>>();
+        self.store.set_selected_keys(&inserted, true);
+        self.store.update_geometry_for_strokes(&inserted);
+        self.store.regenerate_rendering_for_strokes_threaded(
+            self.tasks_tx.clone(),
+            &inserted,
+            self.camera.viewport(),
+            self.camera.image_scale(),
+        );
+
+        widget_flags |= self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport();
+        (inserted, widget_flags)
+    }
+
+    /// Replace the selection with the content of the Svg, fitted into the bounds of the old selection.
+    ///
+    /// The old selection is trashed and the new content becomes the selection.
+    /// Errors if nothing is selected, because the new content would have no place to go.
+    pub fn replace_selection_with(&mut self, svg: &str) -> anyhow::Result<WidgetFlags> {
+        let mut widget_flags = WidgetFlags::default();
+        let selection_keys = self.store.selection_keys_as_rendered();
+        let Some(selection_bounds) = self.store.bounds_for_strokes(&selection_keys) else {
+            return Err(anyhow::anyhow!(
+                "Replacing the selection failed, nothing is selected."
+            ));
+        };
+        let mut vectorimage = VectorImage::from_svg_str(
+            svg,
+            selection_bounds.mins.coords,
+            ImageSizeOption::RespectOriginalSize,
+        )?;
+        // fit into the old selection bounds while keeping the aspect ratio
+        let image_bounds = vectorimage.bounds();
+        let image_extents = image_bounds.extents();
+        if image_extents[0] > 0.0 && image_extents[1] > 0.0 {
+            let ratio = selection_bounds
+                .extents()
+                .component_div(&image_extents)
+                .min();
+            vectorimage.translate(-image_bounds.center().coords);
+            vectorimage.scale(na::Vector2::repeat(ratio));
+            vectorimage.translate(selection_bounds.center().coords);
+        }
+
+        widget_flags |= self.change_pen_style(PenStyle::Selector);
+        self.store.set_selected_keys(&selection_keys, false);
+        self.store.set_trashed_keys(&selection_keys, true);
+        let inserted = self
+            .store
+            .insert_stroke(Stroke::VectorImage(vectorimage), None);
+        self.store.set_selected(inserted, true);
+        self.store.update_geometry_for_strokes(&[inserted]);
+        self.store.regenerate_rendering_for_strokes_threaded(
+            self.tasks_tx.clone(),
+            &[inserted],
+            self.camera.viewport(),
+            self.camera.image_scale(),
+        );
+
+        Ok(widget_flags
+            | self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport())
+    }
 }
@@ -7,7 +7,7 @@ pub mod strokecontent;
 pub mod visual_debug;
 
 // Re-exports
-pub use export::ExportPrefs;
+pub use export::{ExportPrefs, Stamp, SvgExportMetadata};
 use futures::channel::mpsc::UnboundedReceiver;
 use futures::StreamExt;
 pub use import::ImportPrefs;
@@ -15,13 +15,18 @@ pub use snapshot::EngineSnapshot;
 pub use strokecontent::StrokeContent;
 
 // Imports
+use crate::camera::CoordSpace;
 use crate::document::Layout;
-use crate::pens::{Pen, PenStyle};
+use crate::pens::{Pen, PenStyle, SelectionHandles, Selector};
 use crate::pens::{PenMode, PensConfig};
 use crate::store::render_comp::{self, RenderCompState};
-use crate::store::StrokeKey;
+use crate::store::{
+    Alignment, Axis, BoundsChange, Centering, Guide, ResizeAnchor, SelectionDelta,
+    SelectionSnapshot, StrokeKey, StrokeStyleProps, StrokeTransformHook,
+};
 use crate::strokes::content::GeneratedContentImages;
 use crate::strokes::textstroke::{TextAttribute, TextStyle};
+use crate::strokes::{BlendMode, Stroke};
 use crate::{render, AudioPlayer, CloneConfig, SelectionCollision, WidgetFlags};
 use crate::{Camera, Document, PenHolder, StrokeStore};
 use futures::channel::{mpsc, oneshot};
@@ -31,6 +36,7 @@ use rnote_compose::ext::AabbExt;
 use rnote_compose::penevent::{PenEvent, ShortcutKey};
 use rnote_compose::{Color, SplitOrder};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
@@ -70,6 +76,35 @@ impl<'a> EngineViewMut<'a> {
             audioplayer: self.audioplayer,
         }
     }
+
+    /// Rotate the selection by the given angle (in radians) around the center of its bounds.
+    ///
+    /// Updates the geometry, the document size and the rendering of the transformed strokes.
+    /// Shared by [Engine::rotate_selection] and the selector key bindings.
+    /// Returns the new selection bounds, None if no strokes are selected.
+    ///
+    /// The rotation then needs to be recorded.
+    pub(crate) fn rotate_selection(
+        &mut self,
+        angle: f64,
+        keep_upright: &HashSet<StrokeKey>,
+    ) -> (Option<Aabb>, WidgetFlags) {
+        let mut widget_flags = WidgetFlags::default();
+        let Some(new_bounds) = self.store.rotate_selection(angle, keep_upright) else {
+            return (None, widget_flags);
+        };
+        let transformed = self.store.take_transform_dirty_keys();
+        self.store.update_geometry_for_strokes(&transformed);
+        widget_flags |= self.document.resize_autoexpand(self.store, self.camera);
+        self.store.regenerate_rendering_for_strokes_threaded(
+            self.tasks_tx.clone(),
+            &transformed,
+            self.camera.viewport(),
+            self.camera.image_scale(),
+        );
+        widget_flags.store_modified = true;
+        (Some(new_bounds), widget_flags)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -149,6 +184,15 @@ impl EngineTaskReceiver {
     }
 }
 
+/// An in-progress translation of the selection, which gets recorded as one history entry when finished.
+#[derive(Debug, Clone)]
+struct SelectionTranslation {
+    /// The strokes that were selected when the translation began.
+    keys: Vec<StrokeKey>,
+    /// The offset that is already applied to the strokes, relative to their initial positions.
+    applied_offset: na::Vector2<f64>,
+}
+
 /// The engine.
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(default, rename = "engine")]
@@ -176,6 +220,9 @@ pub struct Engine {
     audioplayer: Option<AudioPlayer>,
     #[serde(skip)]
     visual_debug: bool,
+    /// The opacity of the strokes that are not selected while something is selected.
+    #[serde(skip)]
+    nonselected_opacity: f64,
     // the task sender. Must not be modified, only cloned.
     #[serde(skip)]
     tasks_tx: EngineTaskSender,
@@ -193,6 +240,11 @@ pub struct Engine {
     #[cfg(feature = "ui")]
     #[serde(skip)]
     origin_indicator_rendernode: Option<gtk4::gsk::RenderNode>,
+    #[serde(skip)]
+    selection_translation: Option<SelectionTranslation>,
+    /// The union of the selection bounds before and after the most recent selection transform.
+    #[serde(skip)]
+    last_transform_dirty_rect: Option<Aabb>,
 }
 
 impl Default for Engine {
@@ -212,6 +264,7 @@ impl Default for Engine {
 
             audioplayer: None,
             visual_debug: false,
+            nonselected_opacity: 1.0,
             tasks_tx: EngineTaskSender(tasks_tx),
             tasks_rx: Some(EngineTaskReceiver(tasks_rx)),
             background_tile_image: None,
@@ -220,6 +273,8 @@ impl Default for Engine {
             origin_indicator_image: None,
             #[cfg(feature = "ui")]
             origin_indicator_rendernode: None,
+            selection_translation: None,
+            last_transform_dirty_rect: None,
         }
     }
 }
@@ -247,7 +302,6 @@ impl Engine {
         }
     }
 
-    #[allow(unused)]
     pub(crate) fn view_mut(&mut self) -> EngineViewMut {
         EngineViewMut {
             tasks_tx: self.tasks_tx.clone(),
@@ -307,6 +361,26 @@ impl Engine {
         widget_flags
     }
 
+    /// The opacity of the non-selected strokes while something is selected.
+    pub fn nonselected_dim(&self) -> f64 {
+        self.nonselected_opacity
+    }
+
+    /// Dim the strokes that are not selected while something is selected, to focus on the selection.
+    ///
+    /// The factor is the opacity of the non-selected strokes and is clamped to the range 0.0 to 1.0,
+    /// 1.0 clears the dimming. Only the rendering on the canvas is affected, not the strokes or exports.
+    pub fn set_nonselected_dim(&mut self, factor: f64) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        self.nonselected_opacity = if factor.is_finite() {
+            factor.clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        widget_flags.redraw = true;
+        widget_flags
+    }
+
     /// Takes a snapshot of the current state.
     pub fn take_snapshot(&self) -> EngineSnapshot {
         let mut store_history_entry = self.store.create_history_entry();
@@ -321,6 +395,23 @@ impl Engine {
         for key in trashed_keys {
             Arc::make_mut(&mut store_history_entry.stroke_components).remove(key);
         }
+        // Drop the instance links whose instance or source was removed with the trashed strokes
+        let stroke_components = Arc::clone(&store_history_entry.stroke_components);
+        if store_history_entry
+            .instance_components
+            .iter()
+            .any(|(key, instance_comp)| {
+                !stroke_components.contains_key(key)
+                    || !stroke_components.contains_key(instance_comp.source)
+            })
+        {
+            Arc::make_mut(&mut store_history_entry.instance_components).retain(
+                |key, instance_comp| {
+                    stroke_components.contains_key(key)
+                        && stroke_components.contains_key(instance_comp.source)
+                },
+            );
+        }
 
         EngineSnapshot {
             document: self.document.clone_config(),
@@ -328,14 +419,44 @@ impl Engine {
             stroke_components: Arc::clone(&store_history_entry.stroke_components),
             chrono_components: Arc::clone(&store_history_entry.chrono_components),
             chrono_counter: store_history_entry.chrono_counter,
+            tag_components: Arc::clone(&store_history_entry.tag_components),
+            instance_components: Arc::clone(&store_history_entry.instance_components),
+            selection_bookmarks: self.store.selection_bookmarks().clone(),
+            selected_keys: self.store.selection_keys_as_rendered(),
         }
     }
 
     /// Imports an engine snapshot. A save file should always be loaded with this method.
+    ///
+    /// Nothing is selected after loading, even when the snapshot was saved with a selection.
+    /// Use [Engine::load_snapshot_restoring_selection] to restore it.
     pub fn load_snapshot(&mut self, snapshot: EngineSnapshot) -> WidgetFlags {
+        self.import_snapshot(snapshot, false)
+    }
+
+    /// Imports an engine snapshot like [Engine::load_snapshot], but selects the strokes again
+    /// that were selected when the snapshot was taken.
+    ///
+    /// Switches to the selector when a selection was restored.
+    pub fn load_snapshot_restoring_selection(&mut self, snapshot: EngineSnapshot) -> WidgetFlags {
+        self.import_snapshot(snapshot, true)
+    }
+
+    fn import_snapshot(
+        &mut self,
+        snapshot: EngineSnapshot,
+        restore_selection: bool,
+    ) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if restore_selection && !snapshot.selected_keys.is_empty() {
+            // Changing the pen style deselects, so it needs to happen before the selection is restored
+            widget_flags |= self.change_pen_style(PenStyle::Selector);
+        }
         self.document = snapshot.document.clone_config();
         self.camera = snapshot.camera.clone_config();
-        let mut widget_flags = self.store.import_from_snapshot(&snapshot)
+        widget_flags |= self
+            .store
+            .import_from_snapshot(&snapshot, restore_selection)
             | self.doc_resize_autoexpand()
             | self.current_pen_update_state()
             | self.background_rendering_regenerate()
@@ -347,7 +468,23 @@ impl Engine {
 
     /// Records the current store state and saves it as a history entry.
     pub fn record(&mut self, now: Instant) -> WidgetFlags {
-        self.store.record(now)
+        let widget_flags = self.store.record(now);
+        self.regenerate_rendering_synced_instances();
+        widget_flags
+    }
+
+    /// Regenerate the rendering of the instances that were synced with their source strokes when recording.
+    fn regenerate_rendering_synced_instances(&mut self) {
+        let synced_instances = self.store.take_synced_instance_keys();
+        if synced_instances.is_empty() {
+            return;
+        }
+        self.store.regenerate_rendering_for_strokes_threaded(
+            self.engine_tasks_tx(),
+            &synced_instances,
+            self.camera.viewport(),
+            self.camera.image_scale(),
+        );
     }
 
     /// Update the state of the latest history entry with the current document state.
@@ -476,7 +613,7 @@ impl Engine {
         pen_mode: Option<PenMode>,
         now: Instant,
     ) -> (EventPropagation, WidgetFlags) {
-        self.penholder.handle_pen_event(
+        let result = self.penholder.handle_pen_event(
             event,
             pen_mode,
             now,
@@ -488,7 +625,10 @@ impl Engine {
                 camera: &mut self.camera,
                 audioplayer: &mut self.audioplayer,
             },
-        )
+        );
+        // the pens record directly in the store
+        self.regenerate_rendering_synced_instances();
+        result
     }
 
     /// Handle a pressed shortcut key.
@@ -497,7 +637,7 @@ impl Engine {
         shortcut_key: ShortcutKey,
         now: Instant,
     ) -> (EventPropagation, WidgetFlags) {
-        self.penholder.handle_pressed_shortcut_key(
+        let result = self.penholder.handle_pressed_shortcut_key(
             shortcut_key,
             now,
             &mut EngineViewMut {
@@ -508,7 +648,10 @@ impl Engine {
                 camera: &mut self.camera,
                 audioplayer: &mut self.audioplayer,
             },
-        )
+        );
+        // the pens record directly in the store
+        self.regenerate_rendering_synced_instances();
+        result
     }
 
     /// Change the pen style.
@@ -849,8 +992,253 @@ impl Engine {
             | self.update_rendering_current_viewport()
     }
 
+    /// Select the stroke following the current selection in chronological order, wrapping around at the end.
+    pub fn select_next_stroke(&mut self) -> WidgetFlags {
+        let widget_flags = self.change_pen_style(PenStyle::Selector);
+        self.store.select_neighbour_stroke(true);
+        widget_flags
+            | self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Select the stroke preceding the current selection in chronological order, wrapping around at the start.
+    pub fn select_previous_stroke(&mut self) -> WidgetFlags {
+        let widget_flags = self.change_pen_style(PenStyle::Selector);
+        self.store.select_neighbour_stroke(false);
+        widget_flags
+            | self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Select all strokes that overlap with the given reference stroke, excluding the reference itself.
+    pub fn select_overlapping_key(&mut self, reference: StrokeKey) -> WidgetFlags {
+        let widget_flags = self.change_pen_style(PenStyle::Selector);
+        self.store.select_overlapping_key(reference);
+        widget_flags
+            | self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Expand the selection to the entire connected group of strokes that overlap with the selected strokes,
+    /// directly or through other overlapping strokes.
+    pub fn expand_selection_to_touching(&mut self) -> WidgetFlags {
+        let widget_flags = self.change_pen_style(PenStyle::Selector);
+        self.store.expand_selection_to_touching();
+        widget_flags
+            | self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Add the stroke to the selection without deselecting the already selected strokes, e.g. on a modifier-click.
+    pub fn add_stroke_to_selection(&mut self, key: StrokeKey) -> WidgetFlags {
+        let widget_flags = self.change_pen_style(PenStyle::Selector);
+        if !self.store.add_stroke_to_selection(key) {
+            return widget_flags;
+        }
+        widget_flags
+            | self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Remove the stroke from the selection while keeping the other selected strokes, e.g. on a modifier-click.
+    pub fn remove_stroke_from_selection(&mut self, key: StrokeKey) -> WidgetFlags {
+        if !self.store.remove_stroke_from_selection(key) {
+            return WidgetFlags::default();
+        }
+        self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Select all strokes whose largest bounds extent lies within the given range, for cleaning up stray specks or strokes.
+    ///
+    /// A missing limit leaves the range open on that side.
+    pub fn select_strokes_by_size(
+        &mut self,
+        min_extent: Option<f64>,
+        max_extent: Option<f64>,
+    ) -> WidgetFlags {
+        let widget_flags = self.change_pen_style(PenStyle::Selector);
+        self.store.select_strokes_by_size(min_extent, max_extent);
+        widget_flags
+            | self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Select all strokes whose bounds width to height ratio lies within the given range,
+    /// e.g. a large minimum selects long horizontal strokes like underlines.
+    pub fn select_by_aspect_ratio(&mut self, min: f64, max: f64) -> WidgetFlags {
+        let widget_flags = self.change_pen_style(PenStyle::Selector);
+        self.store.select_by_aspect_ratio(min, max);
+        widget_flags
+            | self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Hide the selected strokes without trashing them, e.g. to compare with what is underneath,
+    /// or show them again when they are currently hidden. The selection is kept either way.
+    ///
+    /// Returns whether the strokes are now hidden.
+    pub fn toggle_selection_visibility(&mut self) -> (bool, WidgetFlags) {
+        let mut widget_flags = WidgetFlags::default();
+        let hidden = self.store.toggle_selection_visibility();
+        widget_flags.redraw = true;
+        (hidden, widget_flags)
+    }
+
+    /// Restack the selected strokes by a sort key, e.g. by size or by color. Strokes with a lower key get drawn first.
+    ///
+    /// Only the order among the selected strokes changes.
+    pub fn reorder_selection_by(&mut self, key_fn: impl Fn(&Stroke) -> i64) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if self.store.selection_keys_unordered().is_empty() {
+            return widget_flags;
+        }
+        self.store.reorder_selection_by(key_fn);
+        widget_flags.redraw = true;
+        widget_flags.store_modified = true;
+        widget_flags | self.record(Instant::now())
+    }
+
+    /// Cycle the stacking order among the selected strokes, without changing their position
+    /// relative to the not selected strokes.
+    pub fn cycle_selection_order(&mut self, forward: bool) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if self.store.selection_keys_unordered().len() < 2 {
+            return widget_flags;
+        }
+        self.store.cycle_selection_order(forward);
+        widget_flags.redraw = true;
+        widget_flags.store_modified = true;
+        widget_flags | self.record(Instant::now())
+    }
+
+    /// Raise the given selected stroke one step within the selection, without changing the position
+    /// of the selection relative to the not selected strokes.
+    pub fn raise_key_within_selection(&mut self, key: StrokeKey) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if !self.store.raise_key_within_selection(key) {
+            return widget_flags;
+        }
+        widget_flags.redraw = true;
+        widget_flags.store_modified = true;
+        widget_flags | self.record(Instant::now())
+    }
+
+    /// Lower the given selected stroke one step within the selection, without changing the position
+    /// of the selection relative to the not selected strokes.
+    pub fn lower_key_within_selection(&mut self, key: StrokeKey) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if !self.store.lower_key_within_selection(key) {
+            return widget_flags;
+        }
+        widget_flags.redraw = true;
+        widget_flags.store_modified = true;
+        widget_flags | self.record(Instant::now())
+    }
+
+    /// The text of the selected strokes in reading order, e.g. for copying notes as text.
+    ///
+    /// None if the selected strokes don't carry any text.
+    pub fn selection_to_text(&self) -> Option<String> {
+        self.store.selection_text()
+    }
+
+    /// The stroke colors used in the document with near-identical colors grouped together,
+    /// and the number of strokes with each color. Sorted by descending count, images are excluded.
+    pub fn color_histogram(&self) -> Vec<(Color, usize)> {
+        self.store.color_histogram()
+    }
+
+    /// Select all strokes with a stroke color of the given palette entry, as returned by [Engine::color_histogram].
+    pub fn select_by_palette_entry(&mut self, color: Color) -> WidgetFlags {
+        let widget_flags = self.change_pen_style(PenStyle::Selector);
+        self.store.select_by_palette_entry(color);
+        widget_flags
+            | self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Add the tag to the given stroke, e.g. "todo" or "important". Tags are saved with the document.
+    pub fn add_tag(&mut self, key: StrokeKey, tag: String) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if !self.store.add_tag(key, tag) {
+            return widget_flags;
+        }
+        widget_flags.store_modified = true;
+        widget_flags | self.record(Instant::now())
+    }
+
+    /// Select all strokes in the document that have the given tag.
+    pub fn select_strokes_by_tag(&mut self, tag: &str) -> WidgetFlags {
+        let widget_flags = self.change_pen_style(PenStyle::Selector);
+        self.store.select_strokes_by_tag(tag);
+        widget_flags
+            | self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Store the keys of the current selection under the given name, replacing an existing bookmark with that name.
+    ///
+    /// Bookmarks are saved with the document.
+    pub fn bookmark_selection(&mut self, name: &str) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        self.store.bookmark_selection(name);
+        widget_flags.store_modified = true;
+        widget_flags
+    }
+
+    /// Replace the current selection with the strokes of the bookmark with the given name.
+    ///
+    /// Does nothing if no such bookmark exists.
+    pub fn select_bookmark(&mut self, name: &str) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        if !self.store.has_selection_bookmark(name) {
+            return widget_flags;
+        }
+        widget_flags |= self.change_pen_style(PenStyle::Selector);
+        self.store.select_bookmark(name);
+        widget_flags
+            | self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Select all strokes that are entirely outside the given region, for example to clean up stray content.
+    pub fn select_strokes_outside(&mut self, region: Aabb) -> WidgetFlags {
+        let widget_flags = self.change_pen_style(PenStyle::Selector);
+        self.store.select_strokes_outside(region);
+        widget_flags
+            | self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
     pub fn duplicate_selection(&mut self) -> WidgetFlags {
-        let new_selected = self.store.duplicate_selection();
+        let new_selected = self
+            .store
+            .duplicate_selection(self.pens_config.selector_config.duplication_offset);
         self.store.update_geometry_for_strokes(&new_selected);
         self.current_pen_update_state()
             | self.doc_resize_autoexpand()
@@ -858,6 +1246,443 @@ impl Engine {
             | self.update_rendering_current_viewport()
     }
 
+    /// Set the offset of duplicated strokes relative to their originals,
+    /// e.g. straight down so that repeated duplications of list items cascade downwards.
+    pub fn set_duplication_offset(&mut self, offset: na::Vector2<f64>) {
+        self.pens_config.selector_config.duplication_offset = offset;
+    }
+
+    /// Duplicate the selection and mirror the duplicates across an axis line, for symmetric drawing.
+    ///
+    /// The axis position is in document coordinates. Both the original and the mirrored strokes are selected afterwards.
+    pub fn mirror_duplicate_selection(&mut self, axis: Axis, axis_pos: f64) -> WidgetFlags {
+        let new_selected = self.store.mirror_duplicate_selection(axis, axis_pos);
+        self.store.update_geometry_for_strokes(&new_selected);
+        self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Duplicate the selection as instances, which follow the changes of their source strokes.
+    ///
+    /// Instances keep their own offset, rotating or scaling them turns them into independent strokes.
+    pub fn duplicate_selection_as_instances(&mut self) -> WidgetFlags {
+        let new_selected = self
+            .store
+            .duplicate_selection_as_instances(self.pens_config.selector_config.duplication_offset);
+        self.store.update_geometry_for_strokes(&new_selected);
+        self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// The source stroke of the given instance, None if the stroke is not an instance.
+    pub fn instance_source(&self, key: StrokeKey) -> Option<StrokeKey> {
+        self.store.instance_source(key)
+    }
+
+    /// Break the link of the given instance to its source, turning it into an independent stroke.
+    pub fn make_instance_unique(&mut self, key: StrokeKey) -> WidgetFlags {
+        if self.store.instance_source(key).is_none() {
+            return WidgetFlags::default();
+        }
+        self.store.make_instances_unique(&[key]);
+        self.record(Instant::now())
+    }
+
+    /// Resize the selection to the given new extents, keeping the given anchor of the selection bounds fixed.
+    ///
+    /// No selected stroke shrinks below `min_extent`, pass zero for unrestricted scaling.
+    /// The pinned strokes don't scale, but move along with the group, e.g. to keep text labels at their size.
+    /// Returns the bounds of the selected strokes before and after the resize.
+    pub fn resize_selection(
+        &mut self,
+        new_extents: na::Vector2<f64>,
+        anchor: ResizeAnchor,
+        min_extent: f64,
+        pinned: &HashSet<StrokeKey>,
+    ) -> (Vec<BoundsChange>, WidgetFlags) {
+        let selection = self.store.selection_keys_unordered();
+        let old_bounds = self.store.keyed_strokes_bounds(&selection);
+        let old_selection_bounds = self.store.bounds_for_strokes(&selection);
+        if self
+            .store
+            .resize_selection(new_extents, anchor, min_extent, pinned)
+            .is_none()
+        {
+            return (vec![], WidgetFlags::default());
+        }
+        self.store.update_geometry_for_strokes(&selection);
+        self.update_last_transform_dirty_rect(&selection, old_selection_bounds);
+        (
+            self.store.bounds_changes(old_bounds),
+            self.current_pen_update_state()
+                | self.doc_resize_autoexpand()
+                | self.record(Instant::now())
+                | self.update_rendering_current_viewport(),
+        )
+    }
+
+    /// Translate the selection by the given offset.
+    ///
+    /// Returns the bounds of the selected strokes before and after the translation,
+    /// for example to compute the region that needs to be redrawn.
+    pub fn translate_selection(
+        &mut self,
+        offset: na::Vector2<f64>,
+    ) -> (Vec<BoundsChange>, WidgetFlags) {
+        let selection = self.store.selection_keys_unordered();
+        if selection.is_empty() {
+            return (vec![], WidgetFlags::default());
+        }
+        let old_bounds = self.store.keyed_strokes_bounds(&selection);
+        let old_selection_bounds = self.store.bounds_for_strokes(&selection);
+        if !self.store.translate_strokes(&selection, offset) {
+            return (vec![], WidgetFlags::default());
+        }
+        self.store.translate_strokes_images(&selection, offset);
+        self.store.set_transform_dirty_for_strokes(&selection);
+        self.update_last_transform_dirty_rect(&selection, old_selection_bounds);
+        (
+            self.store.bounds_changes(old_bounds),
+            self.current_pen_update_state()
+                | self.doc_resize_autoexpand()
+                | self.record(Instant::now())
+                | self.update_rendering_current_viewport(),
+        )
+    }
+
+    /// Straighten the selected lines, arrows and brush strokes whose overall direction is within the threshold angle
+    /// (in degrees) of horizontal or vertical, so that they are exactly axis-aligned. Other strokes are left unchanged.
+    pub fn straighten_selection(&mut self, angle_threshold_deg: f64) -> WidgetFlags {
+        let straightened = self
+            .store
+            .straighten_selection(angle_threshold_deg.to_radians());
+        if straightened.is_empty() {
+            return WidgetFlags::default();
+        }
+        self.store.update_geometry_for_strokes(&straightened);
+        self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Align the selected strokes to the edge or center of a reference stroke, which may or may not be selected.
+    ///
+    /// The reference stays in place and the other selected strokes move.
+    pub fn align_selection_to_key(
+        &mut self,
+        reference: StrokeKey,
+        alignment: Alignment,
+    ) -> WidgetFlags {
+        let moved = self.store.align_selection_to_key(reference, alignment);
+        if moved.is_empty() {
+            return WidgetFlags::default();
+        }
+        self.store.update_geometry_for_strokes(&moved);
+        self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Repair the selected polygon shapes whose outline intersects itself, so that their fill renders correctly.
+    ///
+    /// Returns the number of repaired shapes.
+    pub fn repair_selection_shapes(&mut self) -> (usize, WidgetFlags) {
+        let repaired = self.store.repair_selection_shapes();
+        if repaired.is_empty() {
+            return (0, WidgetFlags::default());
+        }
+        self.store.update_geometry_for_strokes(&repaired);
+        (
+            repaired.len(),
+            self.current_pen_update_state()
+                | self.doc_resize_autoexpand()
+                | self.record(Instant::now())
+                | self.update_rendering_current_viewport(),
+        )
+    }
+
+    /// Join the selected brush strokes whose endpoints are at most `max_gap` apart into continuous strokes.
+    ///
+    /// Strokes that are farther apart stay separate.
+    pub fn connect_selected_brushstrokes(&mut self, max_gap: f64) -> WidgetFlags {
+        let joined = self.store.connect_selected_brushstrokes(max_gap);
+        if joined.is_empty() {
+            return WidgetFlags::default();
+        }
+        self.store.update_geometry_for_strokes(&joined);
+        self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Smooth the selected brush strokes to reduce the jitter of hand-drawn strokes while preserving their overall shape.
+    ///
+    /// The strength is the number of neighbouring points on each side that every point is averaged with,
+    /// and is rounded to a whole number. Shapes, text and images are untouched.
+    pub fn smooth_selection(&mut self, strength: f64) -> WidgetFlags {
+        if !strength.is_finite() || strength < 0.5 {
+            return WidgetFlags::default();
+        }
+        let smoothed = self.store.smooth_selection(strength.round() as usize);
+        if smoothed.is_empty() {
+            return WidgetFlags::default();
+        }
+        self.store.update_geometry_for_strokes(&smoothed);
+        self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Arrange the selected strokes in a grid with the given number of columns in chronological order,
+    /// starting at the top-left corner of the selection bounds. The strokes keep their size.
+    pub fn arrange_selection_grid(
+        &mut self,
+        cols: usize,
+        spacing: na::Vector2<f64>,
+    ) -> WidgetFlags {
+        let arranged = self.store.arrange_selection_grid(cols, spacing);
+        if arranged.is_empty() {
+            return WidgetFlags::default();
+        }
+        self.store.update_geometry_for_strokes(&arranged);
+        self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Reflow the selected strokes like handwritten words into lines that wrap at the line width,
+    /// in the order they were drawn. Does nothing if fewer than two strokes are selected.
+    pub fn reflow_selection(&mut self, line_width: f64, line_height: f64) -> WidgetFlags {
+        let reflowed = self.store.reflow_selection(line_width, line_height);
+        if reflowed.is_empty() {
+            return WidgetFlags::default();
+        }
+        self.store.update_geometry_for_strokes(&reflowed);
+        self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Move the selected strokes radially apart from the center of the selection until their bounds
+    /// no longer overlap, to see the individual strokes of a cluttered cluster.
+    pub fn explode_selection(&mut self, spacing: f64) -> WidgetFlags {
+        let moved = self.store.explode_selection(spacing);
+        if moved.is_empty() {
+            return WidgetFlags::default();
+        }
+        self.store.update_geometry_for_strokes(&moved);
+        self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Center the selection horizontally, vertically or both on the page that contains the center of the selection.
+    ///
+    /// Does nothing when nothing is selected or the document format has no page size.
+    pub fn center_selection_on_sheet(&mut self, centering: Centering) -> WidgetFlags {
+        let Some(selection_bounds) = self.store.selection_bounds() else {
+            return WidgetFlags::default();
+        };
+        let page_size = na::vector![self.document.format.width(), self.document.format.height()];
+        if page_size[0] <= 0.0 || page_size[1] <= 0.0 {
+            return WidgetFlags::default();
+        }
+        let selection_center = selection_bounds.center().coords;
+        // Pages are aligned to the origin
+        let page_mins = selection_center
+            .component_div(&page_size)
+            .map(f64::floor)
+            .component_mul(&page_size);
+        let mut offset = page_mins + page_size * 0.5 - selection_center;
+        match centering {
+            Centering::Horizontal => offset[1] = 0.0,
+            Centering::Vertical => offset[0] = 0.0,
+            Centering::Both => {}
+        }
+        self.translate_selection(offset).1
+    }
+
+    /// Translate the selection by a fraction of its own extents,
+    /// e.g. `(1.0, 0.0)` moves the selection exactly its own width to the right.
+    ///
+    /// Returns the bounds of the selected strokes before and after the translation.
+    pub fn translate_selection_relative(
+        &mut self,
+        fraction: na::Vector2<f64>,
+    ) -> (Vec<BoundsChange>, WidgetFlags) {
+        let Some(selection_bounds) = self.store.selection_bounds() else {
+            return (vec![], WidgetFlags::default());
+        };
+        self.translate_selection(fraction.component_mul(&selection_bounds.extents()))
+    }
+
+    /// Begin translating the selection, for example at the start of a drag.
+    ///
+    /// Subsequent calls to [Engine::update_translate_selection] are applied relative to the initial positions
+    /// and the whole translation is recorded as one history entry in [Engine::finish_translate_selection].
+    pub fn begin_translate_selection(&mut self) {
+        self.selection_translation = Some(SelectionTranslation {
+            keys: self.store.selection_keys_unordered(),
+            applied_offset: na::Vector2::zeros(),
+        });
+    }
+
+    /// Translate the selection to the total offset relative to the positions when the translation began.
+    ///
+    /// Does nothing when no translation is in progress.
+    pub fn update_translate_selection(&mut self, total_offset: na::Vector2<f64>) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        let Some(translation) = self.selection_translation.as_ref() else {
+            return widget_flags;
+        };
+        let offset = total_offset - translation.applied_offset;
+        let keys = translation.keys.clone();
+        let old_selection_bounds = self.store.bounds_for_strokes(&keys);
+        if !self.store.translate_strokes(&keys, offset) {
+            return widget_flags;
+        }
+        if let Some(translation) = self.selection_translation.as_mut() {
+            translation.applied_offset = total_offset;
+        }
+        self.store.translate_strokes_images(&keys, offset);
+        self.store.set_transform_dirty_for_strokes(&keys);
+        self.update_last_transform_dirty_rect(&keys, old_selection_bounds);
+        widget_flags.redraw = true;
+        widget_flags | self.current_pen_update_state()
+    }
+
+    /// Finish translating the selection, recording the net translation as a single history entry.
+    ///
+    /// Does nothing when no translation is in progress.
+    pub fn finish_translate_selection(&mut self) -> WidgetFlags {
+        let Some(translation) = self.selection_translation.take() else {
+            return WidgetFlags::default();
+        };
+        if translation.applied_offset == na::Vector2::zeros() {
+            return WidgetFlags::default();
+        }
+        self.commit_selection_transform()
+            | self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Rotate the selection by the given angle (in radians) around the center of its bounds.
+    ///
+    /// The strokes to keep upright don't rotate, but move along with the group, e.g. to keep labels readable.
+    pub fn rotate_selection(
+        &mut self,
+        angle: f64,
+        keep_upright: &HashSet<StrokeKey>,
+    ) -> WidgetFlags {
+        let selection = self.store.selection_keys_unordered();
+        let old_selection_bounds = self.store.bounds_for_strokes(&selection);
+        let (new_bounds, widget_flags) = self.view_mut().rotate_selection(angle, keep_upright);
+        if new_bounds.is_none() {
+            return widget_flags;
+        }
+        self.update_last_transform_dirty_rect(&selection, old_selection_bounds);
+        widget_flags
+            | self.current_pen_update_state()
+            | self.record(Instant::now())
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Rotate the selection by `steps` times the given step angle (in degrees).
+    pub fn rotate_selection_by_step(&mut self, steps: i32, step_deg: f64) -> WidgetFlags {
+        self.rotate_selection((f64::from(steps) * step_deg).to_radians(), &HashSet::new())
+    }
+
+    /// The region affected by the most recent transform of the selection,
+    /// the union of the selection bounds before and after the transform.
+    ///
+    /// Can be used to only repaint this region. None if the selection was not transformed yet.
+    pub fn last_transform_dirty_rect(&self) -> Option<Aabb> {
+        self.last_transform_dirty_rect
+    }
+
+    fn update_last_transform_dirty_rect(
+        &mut self,
+        keys: &[StrokeKey],
+        old_selection_bounds: Option<Aabb>,
+    ) {
+        let new_selection_bounds = self.store.bounds_for_strokes(keys);
+        self.last_transform_dirty_rect = match (old_selection_bounds, new_selection_bounds) {
+            (Some(old), Some(new)) => Some(old.merged(&new)),
+            (old, new) => old.or(new),
+        };
+    }
+
+    /// Scale the selection uniformly by the factor around its center, e.g. `1.1` for 10% larger.
+    ///
+    /// The transform is committed right away, so the rendering is regenerated. No-op if no strokes are selected.
+    pub fn resize_selection_by_factor(&mut self, factor: f64) -> WidgetFlags {
+        let Some(selection_bounds) = self.store.selection_bounds() else {
+            return WidgetFlags::default();
+        };
+        if factor <= 0.0 {
+            return WidgetFlags::default();
+        }
+        let (_, widget_flags) = self.resize_selection(
+            selection_bounds.extents() * factor,
+            ResizeAnchor::Center,
+            0.0,
+            &HashSet::new(),
+        );
+        widget_flags | self.commit_selection_transform()
+    }
+
+    /// Commit the transforms of the selection.
+    ///
+    /// Regenerates the rendering for exactly the strokes that were transformed since the last commit,
+    /// so that several transforms can be batched.
+    pub fn commit_selection_transform(&mut self) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        let keys = self.store.take_transform_dirty_keys();
+        if keys.is_empty() {
+            return widget_flags;
+        }
+        self.store.regenerate_rendering_for_strokes_threaded(
+            self.engine_tasks_tx(),
+            &keys,
+            self.camera.viewport(),
+            self.camera.image_scale(),
+        );
+        widget_flags.redraw = true;
+        widget_flags
+    }
+
+    /// Regenerate the rendering of the selected strokes synchronously, in the order they are rendered.
+    ///
+    /// Unlike the threaded regeneration this does not spawn any tasks,
+    /// so the rendering is complete and deterministic when it returns.
+    pub fn regenerate_rendering_for_selection_blocking(&mut self) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        let keys = self.store.selection_keys_as_rendered();
+        if keys.is_empty() {
+            return widget_flags;
+        }
+        self.store.regenerate_rendering_for_strokes(
+            &keys,
+            self.camera.viewport(),
+            self.camera.image_scale(),
+        );
+        widget_flags.redraw = true;
+        widget_flags
+    }
+
     pub fn trash_selection(&mut self) -> WidgetFlags {
         let selection_keys = self.store.selection_keys_as_rendered();
         self.store.set_trashed_keys(&selection_keys, true);
@@ -867,10 +1692,279 @@ impl Engine {
             | self.update_rendering_current_viewport()
     }
 
+    /// Erase the parts of the brush strokes that collide with the eraser bounds, splitting them into separate strokes.
+    ///
+    /// Shape strokes that collide are trashed completely.
+    /// Returns the keys of the newly created split strokes.
+    pub fn erase_within(&mut self, eraser_bounds: Aabb) -> (Vec<StrokeKey>, WidgetFlags) {
+        let colliding_keys = self
+            .store
+            .stroke_keys_as_rendered_intersecting_bounds(eraser_bounds);
+        let (modified_keys, widget_flags) = self
+            .store
+            .split_colliding_strokes(eraser_bounds, eraser_bounds);
+        // the shortened original strokes changed their bounds
+        self.store.update_geometry_for_strokes(&modified_keys);
+        self.store.regenerate_rendering_for_strokes_threaded(
+            self.engine_tasks_tx(),
+            &modified_keys,
+            self.camera.viewport(),
+            self.camera.image_scale(),
+        );
+        let new_keys = modified_keys
+            .into_iter()
+            .filter(|key| !colliding_keys.contains(key))
+            .collect();
+
+        (
+            new_keys,
+            widget_flags
+                | self.current_pen_update_state()
+                | self.doc_resize_autoexpand()
+                | self.record(Instant::now())
+                | self.update_rendering_current_viewport(),
+        )
+    }
+
     pub fn nothing_selected(&self) -> bool {
         self.store.selection_keys_unordered().is_empty()
     }
 
+    /// The bounds of an arbitrary set of strokes, without modifying the selection.
+    ///
+    /// Invalid and trashed keys are skipped, None if none of the keys are valid.
+    pub fn bounds_of_keys(&self, keys: &[StrokeKey]) -> Option<Aabb> {
+        self.store.bounds_of_keys(keys)
+    }
+
+    /// A fingerprint of the selection, which only changes when the selected strokes or their geometry change.
+    ///
+    /// Can be used to decide whether a cached preview of the selection needs to be re-rendered.
+    pub fn selection_fingerprint(&self) -> u64 {
+        self.store.selection_fingerprint()
+    }
+
+    /// A human-readable summary of the selected keys, their stroke types, bounds and chronological ordering,
+    /// and the selection bounds, meant to be attached to bug reports.
+    pub fn debug_dump_selection(&self) -> String {
+        self.store.debug_dump_selection()
+    }
+
+    /// Take a cheap snapshot of the selected keys, to later retrieve the changes with [Engine::selection_delta_since].
+    pub fn selection_snapshot(&self) -> SelectionSnapshot {
+        self.store.selection_snapshot()
+    }
+
+    /// The keys that were added to and removed from the selection since the given snapshot was taken,
+    /// e.g. to only transmit the changes of the selection when syncing it with collaborators.
+    pub fn selection_delta_since(&self, prev: &SelectionSnapshot) -> SelectionDelta {
+        self.store.selection_delta_since(prev)
+    }
+
+    /// The style properties that all selected strokes have in common, properties that differ are None.
+    ///
+    /// None if nothing is selected or the selection contains images.
+    pub fn selection_common_style(&self) -> Option<StrokeStyleProps> {
+        self.store.selection_common_style()
+    }
+
+    /// The bounds of the selection.
+    ///
+    /// None if no strokes are selected.
+    pub fn selection_bounds(&self) -> Option<Aabb> {
+        self.store.selection_bounds()
+    }
+
+    /// The bounds of the selection in the given coordinate space.
+    ///
+    /// None if no strokes are selected.
+    pub fn selection_bounds_in(&self, space: CoordSpace) -> Option<Aabb> {
+        self.store
+            .selection_bounds()
+            .map(|bounds| self.camera.bounds_in(bounds, space))
+    }
+
+    /// The ratio of the horizontal to the vertical scale factor when resizing the selection to the new bounds.
+    ///
+    /// A factor near 1.0 keeps the proportions, a factor far from 1.0 distorts the selected strokes,
+    /// e.g. squashes handwriting, so the UI can warn before such a resize.
+    /// Returns 1.0 if no strokes are selected or either bounds are degenerate.
+    pub fn resize_distortion_factor(&self, new_bounds: Aabb) -> f64 {
+        let Some(selection_bounds) = self.store.selection_bounds() else {
+            return 1.0;
+        };
+        let old_extents = selection_bounds.extents();
+        let new_extents = new_bounds.extents();
+        if old_extents.min() <= 0.0 || new_extents.min() <= 0.0 {
+            return 1.0;
+        }
+        let scale = new_extents.component_div(&old_extents);
+        scale[0] / scale[1]
+    }
+
+    /// The current width to height ratio of the selection bounds, e.g. to display "3:2" while resizing.
+    ///
+    /// None if no strokes are selected or the selection has no height.
+    pub fn current_resize_aspect(&self) -> Option<f64> {
+        let extents = self.store.selection_bounds()?.extents();
+        if extents[1] <= 0.0 {
+            return None;
+        }
+        Some(extents[0] / extents[1])
+    }
+
+    /// The coordinates of the tiles with the given size that the selection bounds overlap, row by row.
+    ///
+    /// Tile (0, 0) starts at the document origin. Empty if no strokes are selected or the tile size is not positive.
+    pub fn selection_tiles_intersecting(&self, tile_size: f64) -> Vec<(i32, i32)> {
+        let Some(selection_bounds) = self.store.selection_bounds() else {
+            return vec![];
+        };
+        if tile_size <= 0.0 {
+            return vec![];
+        }
+        let tile_range = |min: f64, max: f64| {
+            let start = (min / tile_size).floor() as i32;
+            // bounds ending exactly on a tile border don't overlap the next tile
+            let end = ((max / tile_size).ceil() as i32).max(start + 1);
+            start..end
+        };
+        let x_range = tile_range(selection_bounds.mins[0], selection_bounds.maxs[0]);
+        tile_range(selection_bounds.mins[1], selection_bounds.maxs[1])
+            .flat_map(|y| x_range.clone().map(move |x| (x, y)))
+            .collect()
+    }
+
+    /// The bounds of the selection loosened by the given padding on every side, e.g. for a grab region.
+    ///
+    /// Negative paddings are treated as zero. None if no strokes are selected.
+    pub fn selection_bounds_padded(&self, pad: f64) -> Option<Aabb> {
+        self.store
+            .selection_bounds()
+            .map(|bounds| bounds.loosened(pad.max(0.0)))
+    }
+
+    /// The hit regions of the selection handles, in document coordinates.
+    ///
+    /// None if no strokes are selected.
+    pub fn selection_handles(&self) -> Option<SelectionHandles> {
+        self.store
+            .selection_bounds()
+            .map(|bounds| Selector::selection_handles(bounds, &self.camera))
+    }
+
+    /// Whether the selection overlaps with the given non-selected stroke.
+    pub fn selection_overlaps_stroke(&self, key: StrokeKey) -> bool {
+        self.store.selection_overlaps_stroke(key)
+    }
+
+    /// The strokes that overlap the selection bounds but are not selected,
+    /// e.g. to offer including them before the selection is moved away.
+    pub fn strokes_overlapping_selection(&self) -> Vec<StrokeKey> {
+        self.store.strokes_overlapping_selection()
+    }
+
+    /// The offset that would snap the selection edges to the edges of nearby strokes within the given threshold.
+    ///
+    /// Zero if there is no snap candidate.
+    pub fn compute_snap_offset(&self, threshold: f64) -> na::Vector2<f64> {
+        self.store.compute_snap_offset(threshold)
+    }
+
+    /// The alignment guide lines that should be drawn while dragging the selection,
+    /// where its edges or center are within the threshold of the edges or centers of other strokes.
+    pub fn compute_alignment_guides(&self, threshold: f64) -> Vec<Guide> {
+        self.store.compute_alignment_guides(threshold)
+    }
+
+    /// The accumulated transforms of the selected strokes since they were selected.
+    ///
+    /// The stroke geometry is transformed right away, the transforms are not deferred.
+    /// They are meant for renderers that transform previously generated content until it is regenerated.
+    pub fn selection_transforms(&self) -> Vec<(StrokeKey, na::Matrix3<f64>)> {
+        self.store.selection_transforms()
+    }
+
+    /// Reset the accumulated transforms of the selected strokes to identity
+    /// and regenerate the rendering of the affected strokes from their geometry.
+    ///
+    /// The geometry already carries the transforms, so it is left untouched. Strokes with identity transforms are skipped.
+    pub fn reset_selection_transforms(&mut self) -> WidgetFlags {
+        let reset = self.store.reset_selection_transforms();
+        if reset.is_empty() {
+            return WidgetFlags::default();
+        }
+        self.store.set_transform_dirty_for_strokes(&reset);
+        self.commit_selection_transform()
+            | self.current_pen_update_state()
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Resync the selection after the selected strokes were modified outside of the selection methods,
+    /// e.g. by a filter pass.
+    ///
+    /// Rebuilds the geometry of the selected strokes, so that the selection bounds match the content again,
+    /// and regenerates their rendering. Nothing is recorded in the history.
+    pub fn refresh_selection(&mut self) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        let keys = self.store.selection_keys_unordered();
+        if keys.is_empty() {
+            return widget_flags;
+        }
+        self.store.update_geometry_for_strokes(&keys);
+        self.store.set_rendering_dirty_for_strokes(&keys);
+        widget_flags.redraw = true;
+        widget_flags
+            | self.current_pen_update_state()
+            | self.doc_resize_autoexpand()
+            | self.update_rendering_current_viewport()
+    }
+
+    /// Set a hook that gets invoked with every stroke right after it was translated, rotated or scaled,
+    /// for example to mirror the transforms into external state.
+    ///
+    /// None removes the hook.
+    pub fn set_stroke_transform_hook(&mut self, hook: Option<StrokeTransformHook>) {
+        self.store.set_transform_hook(hook);
+    }
+
+    /// Rebuild only the hitboxes of the part of a stroke that was edited in place,
+    /// given by the range of the edited path elements.
+    pub fn rebuild_hitbox_range(
+        &mut self,
+        key: StrokeKey,
+        element_range: std::ops::Range<usize>,
+    ) -> WidgetFlags {
+        self.store.rebuild_hitbox_range(key, element_range);
+        self.update_rendering_current_viewport()
+    }
+
+    /// The minimum gap between the bounds of the two strokes, zero if they overlap, e.g. for measuring the spacing in diagrams.
+    ///
+    /// None if either stroke doesn't exist.
+    pub fn distance_between_keys(&self, a: StrokeKey, b: StrokeKey) -> Option<f64> {
+        self.store.distance_between_keys(a, b)
+    }
+
+    /// The estimated area that is covered by the selected strokes.
+    pub fn selection_ink_area(&self) -> f64 {
+        self.store.selection_ink_area()
+    }
+
+    /// Set how the selected images are composited with the content below them. Other selected strokes are ignored.
+    pub fn set_selection_blend_mode(&mut self, blend_mode: BlendMode) -> WidgetFlags {
+        let mut widget_flags = WidgetFlags::default();
+        let modified = self
+            .store
+            .set_blend_mode_for_strokes(&self.store.selection_keys_unordered(), blend_mode);
+        if modified.is_empty() {
+            return widget_flags;
+        }
+        widget_flags.redraw = true;
+        widget_flags.store_modified = true;
+        widget_flags | self.record(Instant::now())
+    }
+
     pub fn change_selection_stroke_colors(&mut self, stroke_color: Color) -> WidgetFlags {
         self.store
             .change_stroke_colors(&self.store.selection_keys_as_rendered(), stroke_color)
@@ -1000,3 +2094,58 @@ impl Engine {
         widget_flags
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Engine;
+    use crate::pens::PenStyle;
+    use crate::strokes::{BrushStroke, Stroke};
+    use rnote_compose::penpath::Element;
+    use rnote_compose::Style;
+
+    #[test]
+    fn translate_selection_with_nan_offset_leaves_selection_unchanged() {
+        let mut engine = Engine::default();
+        let key = engine.store.insert_stroke(
+            Stroke::BrushStroke(BrushStroke::new(
+                Element::new(na::vector![10.0, 20.0], 1.0),
+                Style::default(),
+            )),
+            None,
+        );
+        engine.store.set_selected(key, true);
+        let bounds = engine.store.selection_bounds();
+
+        let (bounds_changes, widget_flags) = engine.translate_selection(na::vector![f64::NAN, 0.0]);
+
+        assert!(bounds_changes.is_empty());
+        assert!(!widget_flags.redraw);
+        assert_eq!(engine.store.selection_bounds(), bounds);
+        assert!(engine.store.take_transform_dirty_keys().is_empty());
+    }
+
+    #[test]
+    fn load_snapshot_restores_selection_only_when_asked() {
+        let mut engine = Engine::default();
+        let key = engine.store.insert_stroke(
+            Stroke::BrushStroke(BrushStroke::new(
+                Element::new(na::vector![10.0, 20.0], 1.0),
+                Style::default(),
+            )),
+            None,
+        );
+        engine.store.set_selected(key, true);
+        let snapshot = engine.take_snapshot();
+
+        let mut loaded = Engine::default();
+        let _ = loaded.load_snapshot(snapshot.clone());
+        assert!(loaded.nothing_selected());
+
+        let _ = loaded.load_snapshot_restoring_selection(snapshot);
+        assert_eq!(loaded.store.selection_keys_unordered(), vec![key]);
+        assert_eq!(
+            loaded.penholder.current_pen_style_w_override(),
+            PenStyle::Selector
+        );
+    }
+}
@@ -2,17 +2,21 @@
 use crate::document::background;
 use crate::engine::import::XoppImportPrefs;
 use crate::fileformats::{rnoteformat, xoppformat, FileFormatLoader};
-use crate::store::{ChronoComponent, StrokeKey};
+use crate::store::{ChronoComponent, InstanceComponent, StrokeKey, TagComponent};
 use crate::strokes::Stroke;
 use crate::{Camera, Document, Engine};
 use anyhow::Context;
 use futures::channel::oneshot;
 use serde::{Deserialize, Serialize};
 use slotmap::{HopSlotMap, SecondaryMap};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::error;
 
-// An engine snapshot, used when loading/saving the current document from/into a file.
+/// An engine snapshot, used when loading/saving the current document from/into a file.
+///
+/// Only the document content and the keys of the selected strokes are saved, transient state like the trash is not part of it.
+/// The selection is only restored when loading with [`Engine::load_snapshot_restoring_selection()`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename = "engine_snapshot")]
 pub struct EngineSnapshot {
@@ -26,6 +30,15 @@ pub struct EngineSnapshot {
     pub chrono_components: Arc<SecondaryMap<StrokeKey, Arc<ChronoComponent>>>,
     #[serde(rename = "chrono_counter")]
     pub chrono_counter: u32,
+    #[serde(rename = "tag_components")]
+    pub tag_components: Arc<SecondaryMap<StrokeKey, Arc<TagComponent>>>,
+    #[serde(rename = "instance_components", default)]
+    pub instance_components: Arc<SecondaryMap<StrokeKey, Arc<InstanceComponent>>>,
+    #[serde(rename = "selection_bookmarks")]
+    pub selection_bookmarks: HashMap<String, Vec<StrokeKey>>,
+    /// The keys of the strokes that were selected when the snapshot was taken.
+    #[serde(rename = "selected_keys")]
+    pub selected_keys: Vec<StrokeKey>,
 }
 
 impl Default for EngineSnapshot {
@@ -36,6 +49,10 @@ impl Default for EngineSnapshot {
             stroke_components: Arc::new(HopSlotMap::with_key()),
             chrono_components: Arc::new(SecondaryMap::new()),
             chrono_counter: 0,
+            tag_components: Arc::new(SecondaryMap::new()),
+            instance_components: Arc::new(SecondaryMap::new()),
+            selection_bookmarks: HashMap::new(),
+            selected_keys: vec![],
         }
     }
 }
@@ -18,7 +18,7 @@ pub use penbehaviour::PenBehaviour;
 pub use penholder::PenHolder;
 pub use penmode::PenMode;
 pub use pensconfig::PensConfig;
-pub use selector::Selector;
+pub use selector::{SelectionHandles, Selector};
 pub use shaper::Shaper;
 pub use shortcuts::Shortcuts;
 pub use tools::Tools;
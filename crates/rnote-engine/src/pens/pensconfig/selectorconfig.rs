@@ -1,4 +1,5 @@
 // Imports
+use crate::strokes::Stroke;
 use serde::{Deserialize, Serialize};
 
 #[derive(
@@ -49,6 +50,18 @@ pub struct SelectorConfig {
     pub style: SelectorStyle,
     #[serde(rename = "resize_lock_aspectratio")]
     pub resize_lock_aspectratio: bool,
+    /// Whether the selection set is locked, so that selecting neither replaces nor extends it.
+    #[serde(rename = "selection_frozen")]
+    pub selection_frozen: bool,
+    /// Whether only the topmost stroke gets selected when the selector is small, for picking strokes in dense areas.
+    #[serde(rename = "topmost_only")]
+    pub topmost_only: bool,
+    /// The offset of duplicated strokes relative to their originals, so repeated duplications cascade in this direction.
+    #[serde(
+        rename = "duplication_offset",
+        with = "rnote_compose::serialize::na_vector2_f64_dp3"
+    )]
+    pub duplication_offset: na::Vector2<f64>,
 }
 
 impl Default for SelectorConfig {
@@ -56,6 +69,9 @@ impl Default for SelectorConfig {
         Self {
             style: SelectorStyle::default(),
             resize_lock_aspectratio: false,
+            selection_frozen: false,
+            topmost_only: false,
+            duplication_offset: Stroke::IMPORT_OFFSET_DEFAULT,
         }
     }
 }
@@ -5,10 +5,11 @@ mod penevents;
 use super::pensconfig::selectorconfig::SelectorStyle;
 use super::PenBehaviour;
 use super::PenStyle;
+use crate::camera::CoordSpace;
 use crate::engine::{EngineView, EngineViewMut, StrokeContent};
 use crate::render::Svg;
 use crate::snap::SnapCorner;
-use crate::store::StrokeKey;
+use crate::store::{ResizeAnchor, StrokeKey};
 use crate::strokes::Content;
 use crate::{Camera, DrawableOnDoc, Engine, WidgetFlags};
 use futures::channel::oneshot;
@@ -26,6 +27,17 @@ use std::collections::HashSet;
 use std::time::Instant;
 use tracing::error;
 
+/// The hit regions of the selection handles, in document coordinates.
+#[derive(Clone, Copy, Debug)]
+pub struct SelectionHandles {
+    /// The resize handles, in the order top-left, top, top-right, right, bottom-right, bottom, bottom-left, left.
+    pub resize: [Aabb; 8],
+    /// The rotate handle.
+    pub rotate: BoundingSphere,
+    /// The region where the selection can be moved.
+    pub translate: Aabb,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub(super) enum ResizeCorner {
     TopLeft,
@@ -34,6 +46,18 @@ pub(super) enum ResizeCorner {
     BottomRight,
 }
 
+impl ResizeCorner {
+    /// The anchor that stays fixed when resizing from this corner.
+    fn opposite_anchor(self) -> ResizeAnchor {
+        match self {
+            ResizeCorner::TopLeft => ResizeAnchor::BottomRight,
+            ResizeCorner::TopRight => ResizeAnchor::BottomLeft,
+            ResizeCorner::BottomLeft => ResizeAnchor::TopRight,
+            ResizeCorner::BottomRight => ResizeAnchor::TopLeft,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub(super) enum ModifyState {
     Up,
@@ -274,7 +298,7 @@ impl PenBehaviour for Selector {
 
 impl DrawableOnDoc for Selector {
     fn bounds_on_doc(&self, engine_view: &EngineView) -> Option<Aabb> {
-        let total_zoom = engine_view.camera.total_zoom();
+        let camera = &engine_view.camera;
 
         match &self.state {
             SelectorState::Idle => None,
@@ -284,25 +308,41 @@ impl DrawableOnDoc for Selector {
                 if let Some(first) = path_iter.next() {
                     let mut new_bounds = Aabb::from_half_extents(
                         first.pos.into(),
-                        na::Vector2::repeat(Self::OUTLINE_STROKE_WIDTH / total_zoom),
+                        na::Vector2::repeat(camera.convert_length(
+                            Self::OUTLINE_STROKE_WIDTH,
+                            CoordSpace::Surface,
+                            CoordSpace::Document,
+                        )),
                     );
 
                     path_iter.for_each(|element| {
                         let pos_bounds = Aabb::from_half_extents(
                             element.pos.into(),
-                            na::Vector2::repeat(Self::OUTLINE_STROKE_WIDTH / total_zoom),
+                            na::Vector2::repeat(camera.convert_length(
+                                Self::OUTLINE_STROKE_WIDTH,
+                                CoordSpace::Surface,
+                                CoordSpace::Document,
+                            )),
                         );
                         new_bounds.merge(&pos_bounds);
                     });
 
-                    Some(new_bounds.loosened(Self::SELECTING_SINGLE_CIRCLE_RADIUS / total_zoom))
+                    Some(new_bounds.loosened(camera.convert_length(
+                        Self::SELECTING_SINGLE_CIRCLE_RADIUS,
+                        CoordSpace::Surface,
+                        CoordSpace::Document,
+                    )))
                 } else {
                     None
                 }
             }
             SelectorState::ModifySelection {
                 selection_bounds, ..
-            } => Some(selection_bounds.extend_by(Self::RESIZE_NODE_SIZE / total_zoom)),
+            } => Some(selection_bounds.extend_by(camera.convert_extents(
+                Self::RESIZE_NODE_SIZE,
+                CoordSpace::Surface,
+                CoordSpace::Document,
+            ))),
         }
     }
 
@@ -462,12 +502,19 @@ impl Selector {
     const TRANSLATE_OFFSET_THRESHOLD: f64 = 1.414;
     /// The threshold angle (in radians) where above it the rotation is applied.
     const ROTATE_ANGLE_THRESHOLD: f64 = ((2.0 * std::f64::consts::PI) / 360.0) * 0.2;
+    /// The angle (in radians) the selection is rotated by with a single key press.
+    const ROTATE_STEP_ANGLE: f64 = ((2.0 * std::f64::consts::PI) / 360.0) * 5.0;
+    /// The factor the selection is scaled by with a single key press.
+    const RESIZE_STEP_FACTOR: f64 = 1.1;
     /// The outline stroke width when drawing a selection.
     const OUTLINE_STROKE_WIDTH: f64 = 2.0;
     /// The dash pattern while selecting.
     const SELECTING_DASH_PATTERN: [f64; 2] = [12.0, 6.0];
     /// The radius of the circle when selecting in single mode.
     const SELECTING_SINGLE_CIRCLE_RADIUS: f64 = 4.0;
+    /// The size below which the selector path counts as small when only the topmost stroke should be selected.
+    /// In surface coordinates.
+    const TOPMOST_ONLY_SIZE_THRESHOLD: f64 = 24.0;
     /// Resize node size, in surface coordinates.
     const RESIZE_NODE_SIZE: na::Vector2<f64> = na::vector![18.0, 18.0];
     /// Rotate node diameter, in surface coordinates.
@@ -493,47 +540,119 @@ impl Selector {
         }
     }
 
+    /// Whether the bounds of the selector path are below the size threshold for selecting only the topmost stroke.
+    fn selector_path_is_small(path: &[Element], camera: &Camera) -> bool {
+        let mut elements = path.iter();
+        let Some(first) = elements.next() else {
+            return false;
+        };
+        let mut bounds = Aabb::from_half_extents(first.pos.into(), na::Vector2::zeros());
+        for element in elements {
+            bounds.take_point(element.pos.into());
+        }
+        bounds.extents().max()
+            < camera.convert_length(
+                Self::TOPMOST_ONLY_SIZE_THRESHOLD,
+                CoordSpace::Surface,
+                CoordSpace::Document,
+            )
+    }
+
     fn resize_node_bounds(position: ResizeCorner, selection_bounds: Aabb, camera: &Camera) -> Aabb {
-        let total_zoom = camera.total_zoom();
+        let half_extents = camera.convert_extents(
+            Self::RESIZE_NODE_SIZE * 0.5,
+            CoordSpace::Surface,
+            CoordSpace::Document,
+        );
         match position {
             ResizeCorner::TopLeft => Aabb::from_half_extents(
                 na::point![
-                    selection_bounds.mins[0] - Self::RESIZE_NODE_SIZE[0] * 0.5 / total_zoom,
-                    selection_bounds.mins[1] - Self::RESIZE_NODE_SIZE[0] * 0.5 / total_zoom
+                    selection_bounds.mins[0] - half_extents[0],
+                    selection_bounds.mins[1] - half_extents[0]
                 ],
-                Self::RESIZE_NODE_SIZE * 0.5 / total_zoom,
+                half_extents,
             ),
             ResizeCorner::TopRight => Aabb::from_half_extents(
                 na::point![
-                    selection_bounds.maxs[0] + Self::RESIZE_NODE_SIZE[0] * 0.5 / total_zoom,
-                    selection_bounds.mins[1] - Self::RESIZE_NODE_SIZE[0] * 0.5 / total_zoom
+                    selection_bounds.maxs[0] + half_extents[0],
+                    selection_bounds.mins[1] - half_extents[0]
                 ],
-                Self::RESIZE_NODE_SIZE * 0.5 / total_zoom,
+                half_extents,
             ),
             ResizeCorner::BottomLeft => Aabb::from_half_extents(
                 na::point![
-                    selection_bounds.mins[0] - Self::RESIZE_NODE_SIZE[0] * 0.5 / total_zoom,
-                    selection_bounds.maxs[1] + Self::RESIZE_NODE_SIZE[0] * 0.5 / total_zoom
+                    selection_bounds.mins[0] - half_extents[0],
+                    selection_bounds.maxs[1] + half_extents[0]
                 ],
-                Self::RESIZE_NODE_SIZE * 0.5 / total_zoom,
+                half_extents,
             ),
             ResizeCorner::BottomRight => Aabb::from_half_extents(
                 na::point![
-                    selection_bounds.maxs[0] + Self::RESIZE_NODE_SIZE[0] * 0.5 / total_zoom,
-                    selection_bounds.maxs[1] + Self::RESIZE_NODE_SIZE[0] * 0.5 / total_zoom
+                    selection_bounds.maxs[0] + half_extents[0],
+                    selection_bounds.maxs[1] + half_extents[0]
                 ],
-                Self::RESIZE_NODE_SIZE * 0.5 / total_zoom,
+                half_extents,
             ),
         }
     }
 
     fn rotate_node_sphere(selection_bounds: Aabb, camera: &Camera) -> BoundingSphere {
-        let total_zoom = camera.total_zoom();
         let pos = na::point![
-            selection_bounds.maxs[0] + Self::RESIZE_NODE_SIZE[0] / (2.0 * total_zoom),
+            selection_bounds.maxs[0]
+                + camera.convert_length(
+                    Self::RESIZE_NODE_SIZE[0] * 0.5,
+                    CoordSpace::Surface,
+                    CoordSpace::Document
+                ),
             (selection_bounds.maxs[1] + selection_bounds.mins[1]) * 0.5
         ];
-        BoundingSphere::new(pos, Self::ROTATE_NODE_DIAMETER * 0.5 / total_zoom)
+        BoundingSphere::new(
+            pos,
+            camera.convert_length(
+                Self::ROTATE_NODE_DIAMETER * 0.5,
+                CoordSpace::Surface,
+                CoordSpace::Document,
+            ),
+        )
+    }
+
+    /// The hit regions of the handles for the given selection bounds.
+    pub(crate) fn selection_handles(selection_bounds: Aabb, camera: &Camera) -> SelectionHandles {
+        let half_extents = camera.convert_extents(
+            Self::RESIZE_NODE_SIZE * 0.5,
+            CoordSpace::Surface,
+            CoordSpace::Document,
+        );
+        let center = selection_bounds.center();
+        let top_left = Self::resize_node_bounds(ResizeCorner::TopLeft, selection_bounds, camera);
+        let top_right = Self::resize_node_bounds(ResizeCorner::TopRight, selection_bounds, camera);
+        let bottom_left =
+            Self::resize_node_bounds(ResizeCorner::BottomLeft, selection_bounds, camera);
+        let bottom_right =
+            Self::resize_node_bounds(ResizeCorner::BottomRight, selection_bounds, camera);
+        let top =
+            Aabb::from_half_extents(na::point![center[0], top_left.center()[1]], half_extents);
+        let right =
+            Aabb::from_half_extents(na::point![top_right.center()[0], center[1]], half_extents);
+        let bottom =
+            Aabb::from_half_extents(na::point![center[0], bottom_left.center()[1]], half_extents);
+        let left =
+            Aabb::from_half_extents(na::point![top_left.center()[0], center[1]], half_extents);
+
+        SelectionHandles {
+            resize: [
+                top_left,
+                top,
+                top_right,
+                right,
+                bottom_right,
+                bottom,
+                bottom_left,
+                left,
+            ],
+            rotate: Self::rotate_node_sphere(selection_bounds, camera),
+            translate: selection_bounds,
+        }
     }
 
     fn draw_selection_overlay(
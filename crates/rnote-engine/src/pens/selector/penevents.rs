@@ -1,9 +1,10 @@
 // Imports
 use super::{ModifyState, ResizeCorner, Selector, SelectorState};
+use crate::camera::CoordSpace;
 use crate::engine::EngineViewMut;
 use crate::pens::pensconfig::selectorconfig::SelectorStyle;
 use crate::snap::SnapCorner;
-use crate::store::StrokeKey;
+use crate::store::{ResizeAnchor, StrokeKey};
 use crate::{DrawableOnDoc, WidgetFlags};
 use p2d::bounding_volume::Aabb;
 use p2d::query::PointQuery;
@@ -88,8 +89,10 @@ impl Selector {
                             )
                             .pop();
 
-                        if (engine_view.pens_config.selector_config.style == SelectorStyle::Single
-                            || modifier_keys.contains(&ModifierKey::KeyboardShift))
+                        if !engine_view.pens_config.selector_config.selection_frozen
+                            && (engine_view.pens_config.selector_config.style
+                                == SelectorStyle::Single
+                                || modifier_keys.contains(&ModifierKey::KeyboardShift))
                             && key_to_add
                                 .and_then(|key| engine_view.store.selected(key).map(|s| !s))
                                 .unwrap_or(false)
@@ -179,6 +182,8 @@ impl Selector {
                                 current_pos: element.pos,
                                 snap_corner,
                             };
+                        } else if engine_view.pens_config.selector_config.selection_frozen {
+                            // a frozen selection is not reset when clicking outside its bounds
                         } else {
                             // when clicking outside the selection bounds, reset
                             engine_view.store.set_selected_keys(selection, false);
@@ -209,10 +214,14 @@ impl Selector {
                             - snap_corner_pos;
 
                         if offset.magnitude()
-                            > Self::TRANSLATE_OFFSET_THRESHOLD / engine_view.camera.total_zoom()
+                            > engine_view.camera.convert_length(
+                                Self::TRANSLATE_OFFSET_THRESHOLD,
+                                CoordSpace::Surface,
+                                CoordSpace::Document,
+                            )
+                            && engine_view.store.translate_strokes(selection, offset)
                         {
-                            // move selection
-                            engine_view.store.translate_strokes(selection, offset);
+                            // move the rendering and bounds of the selection along with the strokes
                             engine_view
                                 .store
                                 .translate_strokes_images(selection, offset);
@@ -288,18 +297,7 @@ impl Selector {
                             ],
                             ResizeCorner::BottomRight => start_bounds.maxs.coords,
                         };
-                        let pivot = match from_corner {
-                            ResizeCorner::TopLeft => start_bounds.maxs.coords,
-                            ResizeCorner::TopRight => na::vector![
-                                start_bounds.mins.coords[0],
-                                start_bounds.maxs.coords[1]
-                            ],
-                            ResizeCorner::BottomLeft => na::vector![
-                                start_bounds.maxs.coords[0],
-                                start_bounds.mins.coords[1]
-                            ],
-                            ResizeCorner::BottomRight => start_bounds.mins.coords,
-                        };
+                        let pivot = from_corner.opposite_anchor().pos(*start_bounds);
                         let mut offset_to_start = element.pos - *start_pos;
                         if !lock_aspectratio {
                             offset_to_start = engine_view
@@ -323,8 +321,11 @@ impl Selector {
                             let offset_mean = offset_to_start.mean();
                             offset_to_start = start_extents * (offset_mean / start_mean);
                         }
-                        let min_extents = na::Vector2::<f64>::from_element(2.0f64)
-                            / engine_view.camera.total_zoom();
+                        let min_extents = engine_view.camera.convert_extents(
+                            na::Vector2::<f64>::from_element(2.0f64),
+                            CoordSpace::Surface,
+                            CoordSpace::Document,
+                        );
                         let scale = (start_bounds.extents() + offset_to_start)
                             .maxs(&min_extents)
                             .component_div(&selection_bounds.extents());
@@ -410,7 +411,7 @@ impl Selector {
             SelectorState::Selecting { path } => {
                 let mut progress = PenProgress::Finished;
 
-                let new_selection = match engine_view.pens_config.selector_config.style {
+                let mut new_selection = match engine_view.pens_config.selector_config.style {
                     SelectorStyle::Polygon => {
                         if path.len() >= 3 {
                             engine_view
@@ -450,7 +451,8 @@ impl Selector {
                         }
                     }
                     SelectorStyle::IntersectingPath => {
-                        if path.len() >= 3 {
+                        // a single segment is enough to cross strokes
+                        if path.len() >= 2 {
                             engine_view.store.strokes_hitboxes_intersect_path(
                                 path,
                                 engine_view.camera.viewport(),
@@ -460,16 +462,31 @@ impl Selector {
                         }
                     }
                 };
-                if !new_selection.is_empty() {
+                if engine_view.pens_config.selector_config.topmost_only
+                    && Self::selector_path_is_small(path, engine_view.camera)
+                {
+                    // the keys are in chronological order, so the last one is the topmost
+                    if let Some(topmost) = new_selection.pop() {
+                        new_selection = vec![topmost];
+                    }
+                }
+                // the selection can't be modified while it is frozen
+                if !new_selection.is_empty()
+                    && !engine_view.pens_config.selector_config.selection_frozen
+                {
                     engine_view.store.set_selected_keys(&new_selection, true);
                     widget_flags.store_modified = true;
                     widget_flags.deselect_color_setters = true;
 
-                    if let Some(new_bounds) = engine_view.store.bounds_for_strokes(&new_selection) {
+                    // The viewport only restricts which strokes can become newly selected,
+                    // already selected strokes that are off-screen must stay part of the selection.
+                    let selection = engine_view.store.selection_keys_as_rendered();
+
+                    if let Some(new_bounds) = engine_view.store.bounds_for_strokes(&selection) {
                         // Change to the modify state
                         self.state = SelectorState::ModifySelection {
                             modify_state: ModifyState::default(),
-                            selection: new_selection,
+                            selection,
                             selection_bounds: new_bounds,
                         };
                         progress = PenProgress::InProgress;
@@ -615,7 +632,11 @@ impl Selector {
                     progress: PenProgress::InProgress,
                 },
             },
-            SelectorState::ModifySelection { selection, .. } => {
+            SelectorState::ModifySelection {
+                selection,
+                selection_bounds,
+                ..
+            } => {
                 match keyboard_key {
                     KeyboardKey::Unicode('a') => {
                         self.select_all(modifier_keys, engine_view, &mut widget_flags);
@@ -628,7 +649,9 @@ impl Selector {
                     KeyboardKey::Unicode('d') => {
                         //Duplicate selection
                         if modifier_keys.contains(&ModifierKey::KeyboardCtrl) {
-                            let duplicated = engine_view.store.duplicate_selection();
+                            let duplicated = engine_view.store.duplicate_selection(
+                                engine_view.pens_config.selector_config.duplication_offset,
+                            );
                             engine_view.store.update_geometry_for_strokes(&duplicated);
                             engine_view.store.regenerate_rendering_for_strokes_threaded(
                                 engine_view.tasks_tx.clone(),
@@ -647,6 +670,62 @@ impl Selector {
                             progress: PenProgress::Finished,
                         }
                     }
+                    KeyboardKey::Unicode(keychar @ ('[' | ']')) => {
+                        // Rotate selection in steps
+                        let angle = if keychar == '[' {
+                            -Self::ROTATE_STEP_ANGLE
+                        } else {
+                            Self::ROTATE_STEP_ANGLE
+                        };
+                        let (new_bounds, rotate_widget_flags) =
+                            engine_view.rotate_selection(angle, &HashSet::new());
+                        widget_flags |= rotate_widget_flags;
+                        if let Some(new_bounds) = new_bounds {
+                            *selection_bounds = new_bounds;
+
+                            widget_flags |= engine_view.store.record(Instant::now());
+                        }
+                        EventResult {
+                            handled: true,
+                            propagate: EventPropagation::Stop,
+                            progress: PenProgress::InProgress,
+                        }
+                    }
+                    KeyboardKey::Unicode(keychar @ ('+' | '-')) => {
+                        // Resize selection uniformly in steps
+                        let factor = if keychar == '+' {
+                            Self::RESIZE_STEP_FACTOR
+                        } else {
+                            Self::RESIZE_STEP_FACTOR.recip()
+                        };
+                        if let Some(new_bounds) = engine_view.store.resize_selection(
+                            selection_bounds.extents() * factor,
+                            ResizeAnchor::Center,
+                            0.0,
+                            &HashSet::new(),
+                        ) {
+                            engine_view.store.update_geometry_for_strokes(selection);
+                            widget_flags |= engine_view
+                                .document
+                                .resize_autoexpand(engine_view.store, engine_view.camera);
+                            let transformed = engine_view.store.take_transform_dirty_keys();
+                            engine_view.store.regenerate_rendering_for_strokes_threaded(
+                                engine_view.tasks_tx.clone(),
+                                &transformed,
+                                engine_view.camera.viewport(),
+                                engine_view.camera.image_scale(),
+                            );
+                            *selection_bounds = new_bounds;
+
+                            widget_flags |= engine_view.store.record(Instant::now());
+                            widget_flags.store_modified = true;
+                        }
+                        EventResult {
+                            handled: true,
+                            propagate: EventPropagation::Stop,
+                            progress: PenProgress::InProgress,
+                        }
+                    }
                     KeyboardKey::Delete | KeyboardKey::BackSpace => {
                         engine_view.store.set_trashed_keys(selection, true);
                         widget_flags |= super::cancel_selection(selection, engine_view);
@@ -270,6 +270,18 @@ impl Image {
                 .encode_image(&dynamic_image)
                 .context("Encode dynamic image to jpeg failed.")?;
             }
+            image::ImageFormat::Avif => {
+                /// The encoding speed, between 1 (slowest) and 10 (fastest).
+                const AVIF_SPEED: u8 = 4;
+
+                dynamic_image
+                    .write_with_encoder(image::codecs::avif::AvifEncoder::new_with_speed_quality(
+                        &mut bytes_buf,
+                        AVIF_SPEED,
+                        quality.map(|q| q.clamp(0, 100)).unwrap_or(QUALITY_FALLBACK),
+                    ))
+                    .context("Encode dynamic image to avif failed.")?;
+            }
             format => {
                 dynamic_image
                     .write_to(&mut bytes_buf, format)
@@ -1,5 +1,6 @@
 // Imports
 use super::{StrokeKey, StrokeStore};
+use crate::strokes::Stroke;
 use p2d::bounding_volume::Aabb;
 use rayon::slice::ParallelSliceMut;
 use serde::{Deserialize, Serialize};
@@ -83,6 +84,11 @@ impl ChronoComponent {
     pub(crate) fn new(t: u32, layer: StrokeLayer) -> Self {
         Self { t, layer }
     }
+
+    /// The chronological timestamp.
+    pub(crate) fn t(&self) -> u32 {
+        self.t
+    }
 }
 
 /// Systems that are related to their chronological ordering.
@@ -94,6 +100,125 @@ impl StrokeStore {
         }
     }
 
+    /// Restack the selected strokes by the given sort key, strokes with a lower key get drawn first.
+    ///
+    /// The selected strokes swap their chronological timestamps among each other,
+    /// so their position relative to the not selected strokes and their layers stay the same.
+    /// Strokes with equal keys keep their current order.
+    pub(crate) fn reorder_selection_by(&mut self, key_fn: impl Fn(&Stroke) -> i64) {
+        let mut selection = self
+            .selection_keys_as_rendered()
+            .into_iter()
+            .filter_map(|key| Some((key, key_fn(self.stroke_components.get(key)?))))
+            .collect::<Vec<(StrokeKey, i64)>>();
+        let mut timestamps = selection
+            .iter()
+            .filter_map(|(key, _)| Some(self.chrono_components.get(*key)?.t))
+            .collect::<Vec<u32>>();
+        if timestamps.len() != selection.len() {
+            return;
+        }
+        timestamps.sort_unstable();
+        selection.sort_by_key(|(_, sort_key)| *sort_key);
+
+        let chrono_components = Arc::make_mut(&mut self.chrono_components);
+        for ((key, _), t) in selection.into_iter().zip(timestamps) {
+            if let Some(chrono_comp) = chrono_components.get_mut(key) {
+                Arc::make_mut(chrono_comp).t = t;
+            }
+        }
+    }
+
+    /// Cycle the stacking order among the selected strokes by rotating their chronological timestamps.
+    ///
+    /// Moving forward raises every selected stroke by one step and moves the topmost one to the bottom,
+    /// backward does the opposite. The position relative to the not selected strokes stays the same.
+    pub(crate) fn cycle_selection_order(&mut self, forward: bool) {
+        let selection = self.selection_keys_as_rendered();
+        let mut timestamps = selection
+            .iter()
+            .filter_map(|&key| Some(self.chrono_components.get(key)?.t))
+            .collect::<Vec<u32>>();
+        if timestamps.len() != selection.len() || timestamps.len() < 2 {
+            return;
+        }
+        timestamps.sort_unstable();
+        if forward {
+            timestamps.rotate_left(1);
+        } else {
+            timestamps.rotate_right(1);
+        }
+
+        let chrono_components = Arc::make_mut(&mut self.chrono_components);
+        for (key, t) in selection.into_iter().zip(timestamps) {
+            if let Some(chrono_comp) = chrono_components.get_mut(key) {
+                Arc::make_mut(chrono_comp).t = t;
+            }
+        }
+    }
+
+    /// Raise the given selected stroke one step above the next selected stroke in the same layer,
+    /// by swapping their chronological timestamps.
+    ///
+    /// Returns false if the stroke is not selected or already the topmost selected stroke in its layer.
+    pub(crate) fn raise_key_within_selection(&mut self, key: StrokeKey) -> bool {
+        self.swap_chrono_with_selected_neighbour(key, true)
+    }
+
+    /// Lower the given selected stroke one step below the previous selected stroke in the same layer,
+    /// by swapping their chronological timestamps.
+    ///
+    /// Returns false if the stroke is not selected or already the bottommost selected stroke in its layer.
+    pub(crate) fn lower_key_within_selection(&mut self, key: StrokeKey) -> bool {
+        self.swap_chrono_with_selected_neighbour(key, false)
+    }
+
+    /// Swap the timestamps of the given selected stroke and its selected neighbour in rendering order.
+    ///
+    /// Only swaps with a neighbour in the same layer, otherwise the order relative to the not selected strokes would change.
+    fn swap_chrono_with_selected_neighbour(&mut self, key: StrokeKey, up: bool) -> bool {
+        let selection = self.selection_keys_as_rendered();
+        let Some(i) = selection.iter().position(|&k| k == key) else {
+            return false;
+        };
+        let neighbour = if up {
+            selection.get(i + 1)
+        } else {
+            i.checked_sub(1).and_then(|j| selection.get(j))
+        };
+        let (Some(&neighbour), Some(chrono_comp)) =
+            (neighbour, self.chrono_components.get(key).map(|c| **c))
+        else {
+            return false;
+        };
+        let Some(neighbour_chrono_comp) = self.chrono_components.get(neighbour).map(|c| **c) else {
+            return false;
+        };
+        if chrono_comp.layer != neighbour_chrono_comp.layer {
+            return false;
+        }
+
+        let chrono_components = Arc::make_mut(&mut self.chrono_components);
+        if let Some(chrono_comp) = chrono_components.get_mut(key) {
+            Arc::make_mut(chrono_comp).t = neighbour_chrono_comp.t;
+        }
+        if let Some(neighbour_chrono_comp) = chrono_components.get_mut(neighbour) {
+            Arc::make_mut(neighbour_chrono_comp).t = chrono_comp.t;
+        }
+        true
+    }
+
+    /// Returns the selected keys in the order they were created or last moved to the top, ignoring their layers.
+    pub(crate) fn selection_keys_sorted_chrono_t(&self) -> Vec<StrokeKey> {
+        let mut keys = self.selection_keys_unordered();
+        keys.sort_by_key(|&key| {
+            self.chrono_components
+                .get(key)
+                .map(|chrono_comp| chrono_comp.t)
+        });
+        keys
+    }
+
     /// Returns the keys in chronological order, as in first: gets drawn first, last: gets drawn last.
     pub(crate) fn keys_sorted_chrono(&self) -> Vec<StrokeKey> {
         let chrono_components = &self.chrono_components;
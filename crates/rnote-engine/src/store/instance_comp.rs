@@ -0,0 +1,157 @@
+// Imports
+use super::{StrokeKey, StrokeStore};
+use crate::strokes::Stroke;
+use rnote_compose::shapes::Shapeable;
+use rnote_compose::transform::Transformable;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Weak};
+
+/// Links a stroke to a source stroke, so that it mirrors the geometry of the source with an offset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename = "instance_component")]
+pub struct InstanceComponent {
+    #[serde(rename = "source")]
+    pub source: StrokeKey,
+    #[serde(
+        rename = "offset",
+        with = "rnote_compose::serialize::na_vector2_f64_dp3"
+    )]
+    pub offset: na::Vector2<f64>,
+    /// The source stroke that the instance was last synced with, to detect changes of the source.
+    #[serde(skip)]
+    synced_source: Weak<Stroke>,
+}
+
+impl Default for InstanceComponent {
+    fn default() -> Self {
+        Self {
+            source: StrokeKey::default(),
+            offset: na::Vector2::zeros(),
+            synced_source: Weak::new(),
+        }
+    }
+}
+
+/// Systems that are related to stroke instances.
+impl StrokeStore {
+    /// The source stroke of the given instance, None if the stroke is not an instance.
+    pub(crate) fn instance_source(&self, key: StrokeKey) -> Option<StrokeKey> {
+        self.instance_components
+            .get(key)
+            .map(|instance_comp| instance_comp.source)
+    }
+
+    /// Duplicate the selection like [StrokeStore::duplicate_selection], but link the duplicates to their source strokes.
+    ///
+    /// Duplicating an instance links the duplicate to the source of the instance.
+    /// Returns the keys of the new instances, which are now selected.
+    pub(crate) fn duplicate_selection_as_instances(
+        &mut self,
+        offset: na::Vector2<f64>,
+    ) -> Vec<StrokeKey> {
+        let duplicated = self.duplicate_selection_pairs(offset);
+
+        for &(old_key, new_key) in duplicated.iter() {
+            let (source, base_offset) = match self.instance_components.get(old_key) {
+                Some(instance_comp) => (instance_comp.source, instance_comp.offset),
+                None => (old_key, na::Vector2::zeros()),
+            };
+            let Some(source_stroke) = self.stroke_components.get(source) else {
+                continue;
+            };
+            let instance_comp = InstanceComponent {
+                source,
+                offset: base_offset + offset,
+                synced_source: Arc::downgrade(source_stroke),
+            };
+            Arc::make_mut(&mut self.instance_components).insert(new_key, Arc::new(instance_comp));
+        }
+
+        duplicated.into_iter().map(|(_, new_key)| new_key).collect()
+    }
+
+    /// Take the keys of the instances that were synced when recording, but whose rendering was not yet regenerated.
+    pub(crate) fn take_synced_instance_keys(&mut self) -> Vec<StrokeKey> {
+        std::mem::take(&mut self.synced_instance_keys)
+    }
+
+    /// Break the link of the given instances to their source, turning them into independent strokes.
+    pub(crate) fn make_instances_unique(&mut self, keys: &[StrokeKey]) {
+        if !keys
+            .iter()
+            .any(|&key| self.instance_components.contains_key(key))
+        {
+            return;
+        }
+        let instance_components = Arc::make_mut(&mut self.instance_components);
+        for &key in keys {
+            instance_components.remove(key);
+        }
+    }
+
+    /// Add the offset to the instance offsets of the given strokes, when they got translated by it.
+    pub(crate) fn translate_instance_offsets(
+        &mut self,
+        keys: &[StrokeKey],
+        offset: na::Vector2<f64>,
+    ) {
+        for &key in keys {
+            if !self.instance_components.contains_key(key) {
+                continue;
+            }
+            if let Some(instance_comp) = Arc::make_mut(&mut self.instance_components)
+                .get_mut(key)
+                .map(Arc::make_mut)
+            {
+                instance_comp.offset += offset;
+            }
+        }
+    }
+
+    /// Update the instances whose source stroke has changed since they were last synced.
+    ///
+    /// Instances of trashed or removed sources keep their current geometry.
+    /// Returns the keys of the updated instances.
+    ///
+    /// The instances then need to update their rendering.
+    pub(crate) fn sync_instances(&mut self) -> Vec<StrokeKey> {
+        let outdated = self
+            .instance_components
+            .iter()
+            .filter_map(|(key, instance_comp)| {
+                if !self.stroke_components.contains_key(key)
+                    || self.trashed(instance_comp.source).unwrap_or(true)
+                {
+                    return None;
+                }
+                let source_stroke = self.stroke_components.get(instance_comp.source)?;
+                if std::ptr::eq(
+                    Arc::as_ptr(source_stroke),
+                    instance_comp.synced_source.as_ptr(),
+                ) {
+                    return None;
+                }
+                Some((key, Arc::clone(source_stroke), instance_comp.offset))
+            })
+            .collect::<Vec<(StrokeKey, Arc<Stroke>, na::Vector2<f64>)>>();
+
+        outdated
+            .into_iter()
+            .filter_map(|(key, source_stroke, offset)| {
+                let mut instance_stroke = (*source_stroke).clone();
+                instance_stroke.translate(offset);
+                let bounds = instance_stroke.bounds();
+                *Arc::make_mut(&mut self.stroke_components).get_mut(key)? =
+                    Arc::new(instance_stroke);
+                self.key_tree.update_with_key(key, bounds);
+                if let Some(instance_comp) = Arc::make_mut(&mut self.instance_components)
+                    .get_mut(key)
+                    .map(Arc::make_mut)
+                {
+                    instance_comp.synced_source = Arc::downgrade(&source_stroke);
+                }
+                Some(key)
+            })
+            .collect()
+    }
+}
@@ -1,27 +1,36 @@
 // Modules
 pub mod chrono_comp;
+pub mod instance_comp;
 pub mod keytree;
 pub mod render_comp;
 pub mod selection_comp;
 pub mod stroke_comp;
+pub mod tag_comp;
 pub mod trash_comp;
 
 // Re-exports
 pub use chrono_comp::ChronoComponent;
+pub use instance_comp::InstanceComponent;
 use keytree::KeyTree;
 pub use render_comp::RenderComponent;
-pub use selection_comp::SelectionComponent;
+pub use selection_comp::{
+    Alignment, Axis, Centering, Guide, ResizeAnchor, SelectionComponent, SelectionDelta,
+    SelectionSnapshot, StrokeStyleProps,
+};
+pub use tag_comp::TagComponent;
 pub use trash_comp::TrashComponent;
 
 // Imports
 use self::chrono_comp::StrokeLayer;
+use self::selection_comp::SelectionComponents;
 use crate::engine::EngineSnapshot;
 use crate::strokes::Stroke;
 use crate::WidgetFlags;
+use p2d::bounding_volume::Aabb;
 use rnote_compose::shapes::Shapeable;
 use serde::{Deserialize, Serialize};
 use slotmap::{HopSlotMap, SecondaryMap};
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use std::time::Instant;
 use tracing::debug;
@@ -30,6 +39,26 @@ slotmap::new_key_type! {
     pub struct StrokeKey;
 }
 
+/// The bounds of a stroke before and after it was transformed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundsChange {
+    pub key: StrokeKey,
+    pub old_bounds: Aabb,
+    pub new_bounds: Aabb,
+}
+
+/// A hook that gets invoked with every stroke right after its geometry was transformed.
+pub type StrokeTransformHook = Box<dyn FnMut(StrokeKey, &Stroke) + Send + Sync>;
+
+/// Wraps the transform hook, so that the store can still implement Debug.
+struct TransformHook(StrokeTransformHook);
+
+impl std::fmt::Debug for TransformHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TransformHook")
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename = "history_entry")]
 pub struct HistoryEntry {
@@ -41,6 +70,10 @@ pub struct HistoryEntry {
     pub chrono_components: Arc<SecondaryMap<StrokeKey, Arc<ChronoComponent>>>,
     #[serde(rename = "chrono_counter")]
     pub chrono_counter: u32,
+    #[serde(rename = "instance_components")]
+    pub instance_components: Arc<SecondaryMap<StrokeKey, Arc<InstanceComponent>>>,
+    #[serde(rename = "tag_components")]
+    pub tag_components: Arc<SecondaryMap<StrokeKey, Arc<TagComponent>>>,
 }
 
 impl Default for HistoryEntry {
@@ -49,6 +82,8 @@ impl Default for HistoryEntry {
             stroke_components: Arc::new(HopSlotMap::with_key()),
             trash_components: Arc::new(SecondaryMap::new()),
             chrono_components: Arc::new(SecondaryMap::new()),
+            instance_components: Arc::new(SecondaryMap::new()),
+            tag_components: Arc::new(SecondaryMap::new()),
 
             chrono_counter: 0,
         }
@@ -62,6 +97,8 @@ impl Default for HistoryEntry {
 ///     * 'trash_components': Holds state whether the strokes are trashed
 ///     * 'selection_components': Holds state whether the strokes are selected
 ///     * 'chrono_components': Holds state about the chronological ordering
+///     * 'instance_components': Links instances to their source strokes. Only present for strokes that are instances
+///     * 'tag_components': Holds the user defined tags. Only present for strokes that were tagged
 ///     * 'render_components': Holds state about the rendering.
 ///
 /// The systems are implemented as methods on StrokesStore, loosely categorized to the different components (but often modify others as well).
@@ -76,7 +113,7 @@ pub struct StrokeStore {
     #[serde(rename = "trash_components")]
     trash_components: Arc<SecondaryMap<StrokeKey, Arc<TrashComponent>>>,
     #[serde(rename = "selection_components")]
-    selection_components: Arc<SecondaryMap<StrokeKey, Arc<SelectionComponent>>>,
+    selection_components: Arc<SelectionComponents>,
     #[serde(rename = "chrono_components")]
     chrono_components: Arc<SecondaryMap<StrokeKey, Arc<ChronoComponent>>>,
     /// Incrementing counter for chrono_components.
@@ -84,6 +121,15 @@ pub struct StrokeStore {
     /// Value must be kept equal to the [ChronoComponent] of the newest inserted or modified stroke.
     #[serde(rename = "chrono_counter")]
     chrono_counter: u32,
+    /// Links instances to their source strokes. Only strokes that are instances have this component.
+    #[serde(rename = "instance_components")]
+    instance_components: Arc<SecondaryMap<StrokeKey, Arc<InstanceComponent>>>,
+    /// User defined tags. Only strokes that were tagged have this component.
+    #[serde(rename = "tag_components")]
+    tag_components: Arc<SecondaryMap<StrokeKey, Arc<TagComponent>>>,
+    /// Named sets of stroke keys that can be selected again later. Not part of the history.
+    #[serde(rename = "selection_bookmarks")]
+    selection_bookmarks: HashMap<String, Vec<StrokeKey>>,
     #[serde(skip)]
     render_components: SecondaryMap<StrokeKey, RenderComponent>,
     #[serde(skip)]
@@ -96,6 +142,20 @@ pub struct StrokeStore {
     /// Needs to be updated with `update_with_key()` when strokes changed their geometry or position!
     #[serde(skip)]
     key_tree: KeyTree,
+    /// The keys of the strokes that were transformed, but their rendering was not yet regenerated.
+    ///
+    /// Consumed when the transform is committed.
+    #[serde(skip)]
+    transform_dirty_keys: HashSet<StrokeKey>,
+    /// Optional hook that gets invoked with every transformed stroke, for mirroring transforms into external state.
+    #[serde(skip)]
+    transform_hook: Option<TransformHook>,
+    /// The keys of the selected strokes that were hidden with `toggle_selection_visibility()`.
+    #[serde(skip)]
+    hidden_selection_keys: Vec<StrokeKey>,
+    /// The instances that were synced with their source when recording, but their rendering was not yet regenerated.
+    #[serde(skip)]
+    synced_instance_keys: Vec<StrokeKey>,
 }
 
 impl Default for StrokeStore {
@@ -103,8 +163,11 @@ impl Default for StrokeStore {
         Self {
             stroke_components: Arc::new(HopSlotMap::with_key()),
             trash_components: Arc::new(SecondaryMap::new()),
-            selection_components: Arc::new(SecondaryMap::new()),
+            selection_components: Arc::new(SelectionComponents::default()),
             chrono_components: Arc::new(SecondaryMap::new()),
+            instance_components: Arc::new(SecondaryMap::new()),
+            tag_components: Arc::new(SecondaryMap::new()),
+            selection_bookmarks: HashMap::new(),
             render_components: SecondaryMap::new(),
 
             // Start off with state in the history
@@ -112,6 +175,10 @@ impl Default for StrokeStore {
             live_index: 0,
 
             key_tree: KeyTree::default(),
+            transform_dirty_keys: HashSet::new(),
+            transform_hook: None,
+            hidden_selection_keys: vec![],
+            synced_instance_keys: vec![],
 
             chrono_counter: 0,
         }
@@ -124,17 +191,33 @@ impl StrokeStore {
 
     /// Import from a engine snapshot. A loaded strokes store should always be imported with this method.
     ///
+    /// The selection and trash components are rebuilt empty, so all imported strokes are deselected and not trashed.
+    /// When `restore_selection` is set, the strokes that were selected when the snapshot was taken are selected again,
+    /// keeping their chronological ordering.
+    ///
     /// The store then needs to update its rendering.
-    pub(crate) fn import_from_snapshot(&mut self, snapshot: &EngineSnapshot) -> WidgetFlags {
+    pub(crate) fn import_from_snapshot(
+        &mut self,
+        snapshot: &EngineSnapshot,
+        restore_selection: bool,
+    ) -> WidgetFlags {
         let mut widget_flags = WidgetFlags::default();
 
         widget_flags |= self.clear();
         self.stroke_components = Arc::clone(&snapshot.stroke_components);
         self.chrono_components = Arc::clone(&snapshot.chrono_components);
         self.chrono_counter = snapshot.chrono_counter;
+        self.tag_components = Arc::clone(&snapshot.tag_components);
+        self.instance_components = Arc::clone(&snapshot.instance_components);
+        self.selection_bookmarks = snapshot.selection_bookmarks.clone();
 
         self.update_geometry_for_strokes(&self.keys_unordered());
         self.rebuild_selection_components_slotmap();
+        if restore_selection {
+            for &key in &snapshot.selected_keys {
+                Arc::make_mut(&mut self.selection_components).set_selected(key, true);
+            }
+        }
         self.rebuild_trash_components_slotmap();
         self.rebuild_render_components_slotmap();
         self.rebuild_rtree();
@@ -159,6 +242,11 @@ impl StrokeStore {
             && Arc::ptr_eq(&self.trash_components, &history_entry.trash_components)
             && Arc::ptr_eq(&self.chrono_components, &history_entry.chrono_components)
             && self.chrono_counter == history_entry.chrono_counter
+            && Arc::ptr_eq(
+                &self.instance_components,
+                &history_entry.instance_components,
+            )
+            && Arc::ptr_eq(&self.tag_components, &history_entry.tag_components)
     }
 
     /// Create a history entry from the current state.
@@ -168,6 +256,8 @@ impl StrokeStore {
             trash_components: Arc::clone(&self.trash_components),
             chrono_components: Arc::clone(&self.chrono_components),
             chrono_counter: self.chrono_counter,
+            instance_components: Arc::clone(&self.instance_components),
+            tag_components: Arc::clone(&self.tag_components),
         }
     }
 
@@ -177,6 +267,8 @@ impl StrokeStore {
         self.trash_components = Arc::clone(&history_entry.trash_components);
         self.chrono_components = Arc::clone(&history_entry.chrono_components);
         self.chrono_counter = history_entry.chrono_counter;
+        self.instance_components = Arc::clone(&history_entry.instance_components);
+        self.tag_components = Arc::clone(&history_entry.tag_components);
 
         // Since we don't store the rtree in the history, we need to rebuild it.
         self.rebuild_rtree();
@@ -190,9 +282,21 @@ impl StrokeStore {
     }
 
     /// Record the current state and save it in the history.
+    ///
+    /// Instances are synced with their source strokes first, their rendering then needs to be regenerated
+    /// with the keys from [StrokeStore::take_synced_instance_keys].
     pub(crate) fn record(&mut self, _now: Instant) -> WidgetFlags {
         let mut widget_flags = WidgetFlags::default();
 
+        // instances follow the changes of their source strokes
+        let synced_instances = self.sync_instances();
+        if !synced_instances.is_empty() {
+            self.update_geometry_for_strokes(&synced_instances);
+            self.set_rendering_dirty_for_strokes(&synced_instances);
+            self.synced_instance_keys.extend(synced_instances);
+            widget_flags.redraw = true;
+        }
+
         if self
             .history
             .back()
@@ -327,8 +431,7 @@ impl StrokeStore {
         self.chrono_counter += 1;
 
         Arc::make_mut(&mut self.trash_components).insert(key, Arc::new(TrashComponent::default()));
-        Arc::make_mut(&mut self.selection_components)
-            .insert(key, Arc::new(SelectionComponent::default()));
+        Arc::make_mut(&mut self.selection_components).insert(key, SelectionComponent::default());
         Arc::make_mut(&mut self.chrono_components).insert(
             key,
             Arc::new(ChronoComponent::new(self.chrono_counter, layer)),
@@ -345,6 +448,12 @@ impl StrokeStore {
         Arc::make_mut(&mut self.trash_components).remove(key);
         Arc::make_mut(&mut self.selection_components).remove(key);
         Arc::make_mut(&mut self.chrono_components).remove(key);
+        if self.instance_components.contains_key(key) {
+            Arc::make_mut(&mut self.instance_components).remove(key);
+        }
+        if self.tag_components.contains_key(key) {
+            Arc::make_mut(&mut self.tag_components).remove(key);
+        }
         self.render_components.remove(key);
 
         self.key_tree.remove_with_key(key);
@@ -359,12 +468,17 @@ impl StrokeStore {
         Arc::make_mut(&mut self.trash_components).clear();
         Arc::make_mut(&mut self.selection_components).clear();
         Arc::make_mut(&mut self.chrono_components).clear();
+        Arc::make_mut(&mut self.instance_components).clear();
+        Arc::make_mut(&mut self.tag_components).clear();
+        self.selection_bookmarks.clear();
 
         self.chrono_counter = 0;
         let widget_flags = self.clear_history(HistoryEntry::default());
 
         self.render_components.clear();
         self.key_tree.clear();
+        self.hidden_selection_keys.clear();
+        self.synced_instance_keys.clear();
 
         widget_flags
     }
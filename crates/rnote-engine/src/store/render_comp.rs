@@ -32,6 +32,8 @@ pub struct RenderComponent {
     pub(super) images: Vec<render::Image>,
     #[cfg(feature = "ui")]
     pub(super) rendernodes: Vec<gtk4::gsk::RenderNode>,
+    /// Whether the stroke is drawn. Hidden strokes are still part of the document.
+    pub(super) render: bool,
 }
 
 impl Default for RenderComponent {
@@ -41,6 +43,7 @@ impl Default for RenderComponent {
             images: vec![],
             #[cfg(feature = "ui")]
             rendernodes: vec![],
+            render: true,
         }
     }
 }
@@ -88,6 +91,56 @@ impl StrokeStore {
         keys.iter().for_each(|&key| self.set_rendering_dirty(key));
     }
 
+    /// Mark the strokes as transformed, their rendering is then regenerated when the transform is committed.
+    pub(crate) fn set_transform_dirty_for_strokes(&mut self, keys: &[StrokeKey]) {
+        self.transform_dirty_keys.extend(keys.iter().copied());
+    }
+
+    /// Take the keys of the strokes that were transformed since the last committed transform.
+    pub(crate) fn take_transform_dirty_keys(&mut self) -> Vec<StrokeKey> {
+        self.transform_dirty_keys
+            .drain()
+            .filter(|&key| self.stroke_components.contains_key(key))
+            .collect()
+    }
+
+    /// Whether the stroke is drawn, false when it is currently hidden.
+    pub(crate) fn rendered(&self, key: StrokeKey) -> bool {
+        self.render_components
+            .get(key)
+            .map_or(true, |render_comp| render_comp.render)
+    }
+
+    /// Hide the selected strokes without trashing them, or show them again when they are currently hidden.
+    ///
+    /// The hidden strokes are remembered, so they are restored even when the selection changed in the meantime.
+    /// Returns whether the strokes are now hidden.
+    pub(crate) fn toggle_selection_visibility(&mut self) -> bool {
+        if !self.hidden_selection_keys.is_empty() {
+            for key in std::mem::take(&mut self.hidden_selection_keys) {
+                if let Some(render_comp) = self.render_components.get_mut(key) {
+                    render_comp.render = true;
+                }
+            }
+            return false;
+        }
+        let hidden = self
+            .selection_keys_unordered()
+            .into_iter()
+            .filter(|&key| {
+                let Some(render_comp) = self.render_components.get_mut(key) else {
+                    return false;
+                };
+                // strokes that are already hidden stay hidden when toggling back
+                let was_rendered = render_comp.render;
+                render_comp.render = false;
+                was_rendered
+            })
+            .collect::<Vec<StrokeKey>>();
+        self.hidden_selection_keys = hidden;
+        !self.hidden_selection_keys.is_empty()
+    }
+
     #[allow(unused)]
     pub(crate) fn holds_images(&self, key: StrokeKey) -> bool {
         self.render_components
@@ -477,24 +530,39 @@ impl StrokeStore {
     }
 
     /// Draw all strokes on the gtk snapshot.
+    ///
+    /// While strokes are selected, the other strokes are drawn with the given opacity.
+    /// Images with a blend mode are blended with all strokes drawn before them.
     #[cfg(feature = "ui")]
     pub(crate) fn draw_strokes_to_gtk_snapshot(
         &self,
         snapshot: &gtk4::Snapshot,
         doc_bounds: Aabb,
         viewport: Aabb,
+        nonselected_opacity: f64,
     ) {
         use crate::ext::{GdkRGBAExt, GrapheneRectExt};
-        use gtk4::{gdk, graphene, prelude::*};
+        use crate::strokes::BlendMode;
+        use gtk4::{gdk, graphene, gsk, prelude::*};
         use rnote_compose::color;
 
         snapshot.push_clip(&graphene::Rect::from_p2d_aabb(doc_bounds));
 
+        let dim_nonselected =
+            nonselected_opacity < 1.0 && !self.selection_keys_unordered().is_empty();
+        // the nodes are collected first, so that blended strokes can use everything below them as their backdrop
+        let mut nodes: Vec<gsk::RenderNode> = vec![];
+
         for key in self.stroke_keys_as_rendered_intersecting_bounds(viewport) {
             if let (Some(stroke), Some(render_comp)) = (
                 self.stroke_components.get(key),
                 self.render_components.get(key),
             ) {
+                if !render_comp.render {
+                    continue;
+                }
+                let mut stroke_nodes = vec![];
+
                 // if the stroke currently does not have a rendering and is will create one,
                 // draw a placeholder filled rect
                 if render_comp.rendernodes.is_empty()
@@ -503,18 +571,38 @@ impl StrokeStore {
                         RenderCompState::Dirty | RenderCompState::BusyRenderingInTask
                     )
                 {
-                    snapshot.append_color(
-                        &gdk::RGBA::from_piet_color(color::GNOME_BRIGHTS[1].with_alpha(0.13)),
-                        &graphene::Rect::from_p2d_aabb(stroke.bounds()),
+                    stroke_nodes.push(
+                        gsk::ColorNode::new(
+                            &gdk::RGBA::from_piet_color(color::GNOME_BRIGHTS[1].with_alpha(0.13)),
+                            &graphene::Rect::from_p2d_aabb(stroke.bounds()),
+                        )
+                        .upcast(),
                     );
                 }
+                stroke_nodes.extend(render_comp.rendernodes.iter().cloned());
+
+                let mut stroke_node = gsk::ContainerNode::new(&stroke_nodes).upcast();
+                if dim_nonselected && !self.selected(key).unwrap_or(false) {
+                    stroke_node =
+                        gsk::OpacityNode::new(&stroke_node, nonselected_opacity as f32).upcast();
+                }
 
-                for rendernode in render_comp.rendernodes.iter() {
-                    snapshot.append_node(rendernode);
+                let blend_mode = stroke.blend_mode();
+                if blend_mode != BlendMode::Normal && !nodes.is_empty() {
+                    let backdrop = gsk::ContainerNode::new(&nodes).upcast();
+                    nodes = vec![
+                        gsk::BlendNode::new(&backdrop, &stroke_node, blend_mode.to_gsk()).upcast(),
+                    ];
+                } else {
+                    nodes.push(stroke_node);
                 }
             }
         }
 
+        for node in nodes.iter() {
+            snapshot.append_node(node);
+        }
+
         snapshot.pop();
     }
 
@@ -3,20 +3,450 @@ use super::render_comp::RenderCompState;
 use super::{StrokeKey, StrokeStore};
 use crate::strokes::content::GeneratedContentImages;
 use crate::strokes::Stroke;
-use p2d::bounding_volume::Aabb;
-use serde::{Deserialize, Serialize};
+use p2d::bounding_volume::{Aabb, BoundingVolume};
+use rnote_compose::ext::AabbExt;
+use rnote_compose::shapes::{Shape, Shapeable};
+use rnote_compose::{Color, PenPath, Style, Transform};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use slotmap::{Key, SecondaryMap};
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
+use tracing::warn;
 
+/// The selection component.
+///
+/// All fields must fall back to a default when missing, so that documents saved before a field was added still load.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(default, rename = "selection_component")]
 pub struct SelectionComponent {
+    /// The version of the component layout, zero when loaded from documents that predate versioning.
+    #[serde(default, rename = "version")]
+    pub version: u32,
     #[serde(default, rename = "selected")]
     pub selected: bool,
+    /// The accumulated transform that was applied to the stroke geometry since it was selected.
+    #[serde(skip)]
+    pub transform: Transform,
 }
 
 impl Default for SelectionComponent {
     fn default() -> Self {
-        Self { selected: false }
+        Self {
+            version: Self::VERSION,
+            selected: false,
+            transform: Transform::default(),
+        }
+    }
+}
+
+impl SelectionComponent {
+    /// The current version of the component layout. Must be incremented when fields are added.
+    pub const VERSION: u32 = 1;
+}
+
+/// A set of stroke keys, backed by a bitset over the slot indices of the keys.
+///
+/// Membership tests, scans and set operations only touch the bits of the set, so they stay cheap
+/// in documents with many strokes.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SelectionBitset {
+    words: Vec<u64>,
+    /// The key that occupies each slot, to tell apart the keys of reused slots.
+    keys: Vec<StrokeKey>,
+}
+
+impl PartialEq for SelectionBitset {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for SelectionBitset {}
+
+impl SelectionBitset {
+    /// The slot index of the key in the stroke slotmap.
+    fn slot(key: StrokeKey) -> usize {
+        // The lower 32 bits of the ffi representation are the slot index, the upper 32 bits the version.
+        (key.data().as_ffi() & u64::from(u32::MAX)) as usize
+    }
+
+    pub(crate) fn contains(&self, key: StrokeKey) -> bool {
+        let slot = Self::slot(key);
+        self.words
+            .get(slot / 64)
+            .map_or(false, |word| word & (1_u64 << (slot % 64)) != 0)
+            && self.keys[slot] == key
+    }
+
+    pub(crate) fn insert(&mut self, key: StrokeKey) {
+        let slot = Self::slot(key);
+        if slot >= self.keys.len() {
+            self.keys.resize(slot + 1, StrokeKey::null());
+            self.words.resize(slot / 64 + 1, 0);
+        }
+        self.words[slot / 64] |= 1_u64 << (slot % 64);
+        self.keys[slot] = key;
+    }
+
+    pub(crate) fn remove(&mut self, key: StrokeKey) {
+        if self.contains(key) {
+            let slot = Self::slot(key);
+            self.words[slot / 64] &= !(1_u64 << (slot % 64));
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.words.clear();
+        self.keys.clear();
+    }
+
+    /// The keys in the set, ordered by their slot index.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = StrokeKey> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .filter(|(_, &word)| word != 0)
+            .flat_map(move |(i, &word)| {
+                (0..64)
+                    .filter(move |bit| word & (1_u64 << bit) != 0)
+                    .map(move |bit| self.keys[i * 64 + bit])
+            })
+    }
+
+    /// The keys that are in this set, but not in the other set.
+    pub(crate) fn difference<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> impl Iterator<Item = StrokeKey> + 'a {
+        self.iter().filter(|&key| !other.contains(key))
+    }
+}
+
+/// The selection components of the strokes, together with a bitset of the selected keys.
+///
+/// Serializes as the plain map of components, the bitset is rebuilt from them when loading.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SelectionComponents {
+    components: SecondaryMap<StrokeKey, Arc<SelectionComponent>>,
+    selected: SelectionBitset,
+}
+
+impl Serialize for SelectionComponents {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.components.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SelectionComponents {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let components =
+            SecondaryMap::<StrokeKey, Arc<SelectionComponent>>::deserialize(deserializer)?;
+        let mut selected = SelectionBitset::default();
+        components
+            .iter()
+            .filter(|(_, selection_comp)| selection_comp.selected)
+            .for_each(|(key, _)| selected.insert(key));
+        Ok(Self {
+            components,
+            selected,
+        })
+    }
+}
+
+impl SelectionComponents {
+    pub(crate) fn get(&self, key: StrokeKey) -> Option<&SelectionComponent> {
+        self.components
+            .get(key)
+            .map(|selection_comp| selection_comp.as_ref())
+    }
+
+    pub(crate) fn insert(&mut self, key: StrokeKey, selection_comp: SelectionComponent) {
+        if selection_comp.selected {
+            self.selected.insert(key);
+        } else {
+            self.selected.remove(key);
+        }
+        self.components.insert(key, Arc::new(selection_comp));
+    }
+
+    pub(crate) fn remove(&mut self, key: StrokeKey) {
+        self.selected.remove(key);
+        self.components.remove(key);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.selected.clear();
+        self.components.clear();
+    }
+
+    /// The keys of the selected components.
+    pub(crate) fn selected(&self) -> &SelectionBitset {
+        &self.selected
+    }
+
+    /// Set whether the component is selected, keeping the bitset in sync.
+    ///
+    /// Returns the mutable component, None if there is no component for the key.
+    pub(crate) fn set_selected(
+        &mut self,
+        key: StrokeKey,
+        selected: bool,
+    ) -> Option<&mut SelectionComponent> {
+        let selection_comp = self.components.get_mut(key).map(Arc::make_mut)?;
+        if selected {
+            self.selected.insert(key);
+        } else {
+            self.selected.remove(key);
+        }
+        selection_comp.selected = selected;
+        Some(selection_comp)
+    }
+
+    pub(crate) fn get_mut(&mut self, key: StrokeKey) -> Option<&mut SelectionComponent> {
+        self.components.get_mut(key).map(Arc::make_mut)
+    }
+}
+
+/// The point of the selection bounds that stays fixed while resizing the selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeAnchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+impl Default for ResizeAnchor {
+    fn default() -> Self {
+        Self::TopLeft
+    }
+}
+
+impl ResizeAnchor {
+    /// The position of the anchor on the given bounds.
+    pub fn pos(self, bounds: Aabb) -> na::Vector2<f64> {
+        match self {
+            Self::TopLeft => bounds.mins.coords,
+            Self::TopRight => na::vector![bounds.maxs[0], bounds.mins[1]],
+            Self::BottomLeft => na::vector![bounds.mins[0], bounds.maxs[1]],
+            Self::BottomRight => bounds.maxs.coords,
+            Self::Center => bounds.center().coords,
+        }
+    }
+}
+
+/// The edge or center that strokes are aligned to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    HorizontalCenter,
+    Right,
+    Top,
+    VerticalCenter,
+    Bottom,
+}
+
+impl Alignment {
+    /// The offset that aligns the bounds to the reference bounds.
+    fn offset(self, bounds: Aabb, reference: Aabb) -> na::Vector2<f64> {
+        match self {
+            Self::Left => na::vector![reference.mins[0] - bounds.mins[0], 0.0],
+            Self::HorizontalCenter => na::vector![reference.center()[0] - bounds.center()[0], 0.0],
+            Self::Right => na::vector![reference.maxs[0] - bounds.maxs[0], 0.0],
+            Self::Top => na::vector![0.0, reference.mins[1] - bounds.mins[1]],
+            Self::VerticalCenter => na::vector![0.0, reference.center()[1] - bounds.center()[1]],
+            Self::Bottom => na::vector![0.0, reference.maxs[1] - bounds.maxs[1]],
+        }
+    }
+}
+
+/// The orientation of an axis line, e.g. that strokes are mirrored across or of an alignment guide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// A vertical line at a x position, mirroring from left to right.
+    Vertical,
+    /// A horizontal line at a y position, mirroring from top to bottom.
+    Horizontal,
+}
+
+/// A guide line where an edge or the center of the selection aligns with an edge or the center of another stroke.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Guide {
+    /// The orientation of the guide line.
+    pub axis: Axis,
+    /// The x position of a vertical guide, the y position of a horizontal guide.
+    pub pos: f64,
+    /// Where the guide line starts, so that it spans the selection and all aligned strokes.
+    pub start: f64,
+    /// Where the guide line ends.
+    pub end: f64,
+}
+
+/// The positions of the minimum edge, the center and the maximum edge of the bounds on the given axis index.
+fn alignment_lines(bounds: Aabb, axis_idx: usize) -> [f64; 3] {
+    [
+        bounds.mins[axis_idx],
+        bounds.center()[axis_idx],
+        bounds.maxs[axis_idx],
+    ]
+}
+
+/// The directions in which the selection gets centered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Centering {
+    Horizontal,
+    Vertical,
+    Both,
+}
+
+impl Default for Centering {
+    fn default() -> Self {
+        Self::Both
+    }
+}
+
+/// The maximum difference of each color channel for colors to be grouped into the same palette entry.
+const PALETTE_COLOR_TOLERANCE: f64 = 0.02;
+
+/// The maximum rounds of growing the selection by the touching strokes.
+const EXPAND_SELECTION_MAX_ITERATIONS: usize = 1000;
+
+/// The horizontal gap between reflowed strokes, relative to the line height.
+const REFLOW_WORD_SPACING: f64 = 0.25;
+
+/// The maximum steps a stroke is moved outward when exploding the selection.
+const EXPLODE_SELECTION_MAX_STEPS: usize = 1000;
+
+/// Whether the colors are near-identical and belong to the same palette entry.
+fn palette_colors_match(first: Color, second: Color) -> bool {
+    (first.r - second.r).abs() <= PALETTE_COLOR_TOLERANCE
+        && (first.g - second.g).abs() <= PALETTE_COLOR_TOLERANCE
+        && (first.b - second.b).abs() <= PALETTE_COLOR_TOLERANCE
+        && (first.a - second.a).abs() <= PALETTE_COLOR_TOLERANCE
+}
+
+/// Find the two paths with the closest endpoints within the maximum gap.
+///
+/// Returns the indices `i < j` of the paths and whether path `i` needs to be reversed to end at the connecting endpoint,
+/// and whether path `j` needs to be reversed to start at it.
+fn closest_path_endpoints(
+    paths: &[(StrokeKey, PenPath)],
+    max_gap: f64,
+) -> Option<(usize, usize, bool, bool)> {
+    let mut closest: Option<(f64, (usize, usize, bool, bool))> = None;
+
+    for (i, (_, first)) in paths.iter().enumerate() {
+        for (j, (_, second)) in paths.iter().enumerate().skip(i + 1) {
+            for (first_pos, reverse_first) in [(first.end().pos, false), (first.start.pos, true)] {
+                for (second_pos, reverse_second) in
+                    [(second.start.pos, false), (second.end().pos, true)]
+                {
+                    let gap = (second_pos - first_pos).magnitude();
+                    if gap <= max_gap && closest.map_or(true, |(closest_gap, _)| gap < closest_gap)
+                    {
+                        closest = Some((gap, (i, j, reverse_first, reverse_second)));
+                    }
+                }
+            }
+        }
+    }
+
+    closest.map(|(_, join)| join)
+}
+
+/// Format the bounds as their minimum and maximum corners.
+fn format_bounds(bounds: Aabb) -> String {
+    format!(
+        "({:.3}, {:.3}) - ({:.3}, {:.3})",
+        bounds.mins[0], bounds.mins[1], bounds.maxs[0], bounds.maxs[1]
+    )
+}
+
+/// How the selection geometry was transformed by [StrokeStore::resize_selection_geometry_only],
+/// to apply the same transform to the rendering.
+#[derive(Debug, Clone)]
+pub(crate) struct ResizedSelection {
+    /// The new selection bounds.
+    pub(crate) bounds: Aabb,
+    /// The keys of the strokes that were scaled.
+    pub(crate) scaled_keys: Vec<StrokeKey>,
+    pub(crate) scale: na::Vector2<f64>,
+    pub(crate) pivot: na::Vector2<f64>,
+    /// The pinned strokes, which were only moved by the offset.
+    pub(crate) pinned_offsets: Vec<(StrokeKey, na::Vector2<f64>)>,
+}
+
+/// The selected keys at some point in time, to compute which keys were added or removed since.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectionSnapshot {
+    keys: SelectionBitset,
+}
+
+/// The keys that were added to and removed from the selection since a [SelectionSnapshot] was taken.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectionDelta {
+    pub added: Vec<StrokeKey>,
+    pub removed: Vec<StrokeKey>,
+}
+
+impl SelectionDelta {
+    /// Whether the selection is unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// The style properties that the selected strokes have in common.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StrokeStyleProps {
+    /// The common stroke color, None if it is mixed or not available.
+    pub stroke_color: Option<Color>,
+    /// The common fill color, None if it is mixed or not available.
+    pub fill_color: Option<Color>,
+    /// The common stroke width, None if it is mixed or not available.
+    pub stroke_width: Option<f64>,
+}
+
+impl StrokeStyleProps {
+    /// The style properties of a single stroke, None for images.
+    fn from_stroke(stroke: &Stroke) -> Option<Self> {
+        match stroke {
+            Stroke::BrushStroke(brushstroke) => Some(Self::from_style(&brushstroke.style)),
+            Stroke::ShapeStroke(shapestroke) => Some(Self::from_style(&shapestroke.style)),
+            Stroke::TextStroke(textstroke) => Some(Self {
+                stroke_color: Some(textstroke.text_style.color),
+                fill_color: None,
+                stroke_width: None,
+            }),
+            Stroke::VectorImage(_) | Stroke::BitmapImage(_) => None,
+        }
+    }
+
+    fn from_style(style: &Style) -> Self {
+        Self {
+            stroke_color: style.stroke_color(),
+            fill_color: style.fill_color(),
+            stroke_width: Some(style.stroke_width()),
+        }
+    }
+
+    /// Keep only the properties that are equal to the other properties.
+    fn intersect(self, other: Self) -> Self {
+        Self {
+            stroke_color: self
+                .stroke_color
+                .filter(|_| self.stroke_color == other.stroke_color),
+            fill_color: self
+                .fill_color
+                .filter(|_| self.fill_color == other.fill_color),
+            stroke_width: self.stroke_width.filter(|&width| {
+                other.stroke_width.map_or(false, |other_width| {
+                    approx::relative_eq!(width, other_width)
+                })
+            }),
+        }
     }
 }
 
@@ -24,10 +454,10 @@ impl Default for SelectionComponent {
 impl StrokeStore {
     /// Rebuild the slotmap with empty selection components with the keys returned from the stroke components.
     pub(crate) fn rebuild_selection_components_slotmap(&mut self) {
-        self.selection_components = Arc::new(slotmap::SecondaryMap::new());
+        self.selection_components = Arc::new(SelectionComponents::default());
         self.stroke_components.keys().for_each(|key| {
             Arc::make_mut(&mut self.selection_components)
-                .insert(key, Arc::new(SelectionComponent::default()));
+                .insert(key, SelectionComponent::default());
         });
     }
 
@@ -44,14 +474,55 @@ impl StrokeStore {
     }
 
     /// Set if the stroke is currently selected.
+    ///
+    /// Selecting moves the stroke to the top of the stack, deselecting leaves its chronological ordering untouched.
     pub(crate) fn set_selected(&mut self, key: StrokeKey, selected: bool) {
-        if let Some(selection_comp) = Arc::make_mut(&mut self.selection_components)
-            .get_mut(key)
-            .map(Arc::make_mut)
+        let was_selected = self.selected(key).unwrap_or(false);
+        if let Some(selection_comp) =
+            Arc::make_mut(&mut self.selection_components).set_selected(key, selected)
         {
-            selection_comp.selected = selected;
+            if selected && !was_selected {
+                selection_comp.transform = Transform::default();
+            }
+
+            if selected {
+                self.update_chrono_to_last(key);
+            }
+        }
+    }
+
+    /// Add the stroke to the existing selection.
+    ///
+    /// Trashed and already selected strokes are left untouched. Returns whether the stroke was added.
+    pub(crate) fn add_stroke_to_selection(&mut self, key: StrokeKey) -> bool {
+        if self.trashed(key).unwrap_or(true) || self.selected(key).unwrap_or(true) {
+            return false;
+        }
+        self.set_selected(key, true);
+        true
+    }
+
+    /// Remove the stroke from the existing selection, keeping the other selected strokes.
+    ///
+    /// Returns whether the stroke was removed.
+    pub(crate) fn remove_stroke_from_selection(&mut self, key: StrokeKey) -> bool {
+        if !self.selected(key).unwrap_or(false) {
+            return false;
+        }
+        self.set_selected(key, false);
+        true
+    }
 
-            self.update_chrono_to_last(key);
+    /// Apply the operation on the accumulated transform of the stroke, if it is selected.
+    pub(crate) fn update_selection_transform(
+        &mut self,
+        key: StrokeKey,
+        op: impl FnOnce(&mut Transform),
+    ) {
+        if let Some(selection_comp) = Arc::make_mut(&mut self.selection_components).get_mut(key) {
+            if selection_comp.selected {
+                op(&mut selection_comp.transform);
+            }
         }
     }
 
@@ -62,10 +533,12 @@ impl StrokeStore {
     }
 
     pub(crate) fn selection_keys_unordered(&self) -> Vec<StrokeKey> {
-        self.stroke_components
-            .keys()
+        // Scanning the bitset only visits the selected keys, not the far more common non-selected strokes.
+        self.selection_components
+            .selected()
+            .iter()
             .filter(|&key| {
-                !(self.trashed(key).unwrap_or(false)) && (self.selected(key).unwrap_or(false))
+                self.stroke_components.contains_key(key) && !(self.trashed(key).unwrap_or(false))
             })
             .collect()
     }
@@ -74,32 +547,182 @@ impl StrokeStore {
     ///
     /// Does not return the non-selected stroke keys.
     pub(crate) fn selection_keys_as_rendered(&self) -> Vec<StrokeKey> {
-        let keys_sorted_chrono = self.keys_sorted_chrono();
-
-        keys_sorted_chrono
-            .into_iter()
-            .filter(|&key| {
-                !(self.trashed(key).unwrap_or(false)) && (self.selected(key).unwrap_or(false))
-            })
-            .collect::<Vec<StrokeKey>>()
+        let mut keys = self.selection_keys_unordered();
+        keys.sort_unstable_by_key(|&key| {
+            self.chrono_components
+                .get(key)
+                .map(|chrono_comp| (chrono_comp.layer, chrono_comp.t()))
+        });
+        keys
     }
 
     /// Generate the bounds that include all selected strokes.
     ///
     /// None if no strokes are selected
-    #[allow(unused)]
     pub(crate) fn selection_bounds(&self) -> Option<Aabb> {
         self.bounds_for_strokes(&self.selection_keys_unordered())
     }
 
-    /// Duplicate the selected keys.
+    /// A fingerprint of the selection, hashing the selected keys together with their chronological timestamps and bounds.
+    ///
+    /// Stays the same as long as the selection and the geometry of the selected strokes don't change,
+    /// so it can be used to cache rendered previews of the selection.
+    pub(crate) fn selection_fingerprint(&self) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        for key in self.selection_keys_unordered() {
+            key.hash(&mut hasher);
+            if let Some(chrono_comp) = self.chrono_components.get(key) {
+                chrono_comp.t().hash(&mut hasher);
+            }
+            if let Some(stroke) = self.stroke_components.get(key) {
+                let bounds = stroke.bounds();
+                for coord in bounds.mins.iter().chain(bounds.maxs.iter()) {
+                    coord.to_bits().hash(&mut hasher);
+                }
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Take a snapshot of the currently selected keys.
+    pub(crate) fn selection_snapshot(&self) -> SelectionSnapshot {
+        let mut keys = SelectionBitset::default();
+        self.selection_keys_unordered()
+            .into_iter()
+            .for_each(|key| keys.insert(key));
+        SelectionSnapshot { keys }
+    }
+
+    /// The named selection bookmarks.
+    pub(crate) fn selection_bookmarks(&self) -> &HashMap<String, Vec<StrokeKey>> {
+        &self.selection_bookmarks
+    }
+
+    /// Whether a selection bookmark with the given name exists.
+    pub(crate) fn has_selection_bookmark(&self, name: &str) -> bool {
+        self.selection_bookmarks.contains_key(name)
+    }
+
+    /// Store the keys of the current selection under the given name, replacing an existing bookmark with that name.
+    pub(crate) fn bookmark_selection(&mut self, name: &str) {
+        let keys = self.selection_keys_as_rendered();
+        self.selection_bookmarks.insert(name.to_string(), keys);
+    }
+
+    /// Replace the selection with the strokes of the bookmark with the given name.
+    ///
+    /// Keys of strokes that were trashed or removed since the bookmark was stored are skipped.
+    /// Returns the newly selected keys, None if no such bookmark exists.
+    pub(crate) fn select_bookmark(&mut self, name: &str) -> Option<Vec<StrokeKey>> {
+        let keys = self
+            .selection_bookmarks
+            .get(name)?
+            .iter()
+            .copied()
+            .filter(|&key| !self.trashed(key).unwrap_or(true))
+            .collect::<Vec<StrokeKey>>();
+        self.set_selected_keys(&self.selection_keys_unordered(), false);
+        self.set_selected_keys(&keys, true);
+        Some(keys)
+    }
+
+    /// The keys that were added to and removed from the selection since the given snapshot was taken, each sorted.
+    pub(crate) fn selection_delta_since(&self, prev: &SelectionSnapshot) -> SelectionDelta {
+        let current = self.selection_snapshot();
+        let mut added = current
+            .keys
+            .difference(&prev.keys)
+            .collect::<Vec<StrokeKey>>();
+        let mut removed = prev
+            .keys
+            .difference(&current.keys)
+            .collect::<Vec<StrokeKey>>();
+        added.sort_unstable();
+        removed.sort_unstable();
+        SelectionDelta { added, removed }
+    }
+
+    /// A human-readable summary of the selection state with the type, bounds and chronological ordering
+    /// of every selected stroke, meant to be attached to bug reports.
+    ///
+    /// Inconsistent state like selected keys without a stroke or without a chrono component is reported, not skipped.
+    pub(crate) fn debug_dump_selection(&self) -> String {
+        let selected = self
+            .selection_components
+            .selected()
+            .iter()
+            .collect::<Vec<StrokeKey>>();
+        let mut dump = format!("selected keys: {}\n", selected.len());
+
+        for key in selected {
+            let stroke = self.stroke_components.get(key);
+            let stroke_type = match stroke.map(|stroke| stroke.as_ref()) {
+                Some(Stroke::BrushStroke(_)) => "brushstroke",
+                Some(Stroke::ShapeStroke(_)) => "shapestroke",
+                Some(Stroke::TextStroke(_)) => "textstroke",
+                Some(Stroke::VectorImage(_)) => "vectorimage",
+                Some(Stroke::BitmapImage(_)) => "bitmapimage",
+                None => "missing stroke",
+            };
+            let bounds = stroke.map_or(String::from("none"), |stroke| {
+                format_bounds(stroke.bounds())
+            });
+            let chrono = self
+                .chrono_components
+                .get(key)
+                .map_or(String::from("missing"), |chrono_comp| {
+                    format!("t {}, layer {:?}", chrono_comp.t(), chrono_comp.layer)
+                });
+            let trashed = if self.trashed(key).unwrap_or(false) {
+                ", trashed"
+            } else {
+                ""
+            };
+            dump.push_str(&format!(
+                "  {key:?}: {stroke_type}, bounds: {bounds}, chrono: {chrono}{trashed}\n"
+            ));
+        }
+
+        let selection_bounds = self
+            .selection_bounds()
+            .map_or(String::from("none"), format_bounds);
+        dump.push_str(&format!("selection bounds: {selection_bounds}\n"));
+        dump
+    }
+
+    /// The style properties that all selected strokes have in common.
+    ///
+    /// Properties that differ between the strokes are None.
+    /// None if nothing is selected or the selection contains images, which don't have a stroke style.
+    pub(crate) fn selection_common_style(&self) -> Option<StrokeStyleProps> {
+        self.selection_keys_unordered()
+            .into_iter()
+            .filter_map(|key| self.stroke_components.get(key))
+            .map(|stroke| StrokeStyleProps::from_stroke(stroke))
+            .reduce(|acc, props| Some(acc?.intersect(props?)))
+            .flatten()
+    }
+
+    /// Duplicate the selected keys, offsetting the duplicates to make the duplication apparent.
     ///
     /// The returned, duplicated strokes then need to update their geometry and rendering.
-    pub(crate) fn duplicate_selection(&mut self) -> Vec<StrokeKey> {
+    pub(crate) fn duplicate_selection(&mut self, offset: na::Vector2<f64>) -> Vec<StrokeKey> {
+        self.duplicate_selection_pairs(offset)
+            .into_iter()
+            .map(|(_, new_key)| new_key)
+            .collect()
+    }
+
+    /// Duplicate the selected keys like [StrokeStore::duplicate_selection],
+    /// returning the pairs of the original and the duplicated key.
+    pub(crate) fn duplicate_selection_pairs(
+        &mut self,
+        offset: na::Vector2<f64>,
+    ) -> Vec<(StrokeKey, StrokeKey)> {
         let old_selected = self.selection_keys_as_rendered();
         self.set_selected_keys(&old_selected, false);
 
-        let new_selected = old_selected
+        let duplicated = old_selected
             .iter()
             .filter_map(|&old_key| {
                 let new_key =
@@ -121,14 +744,1361 @@ impl StrokeStore {
                         );
                     }
                 }
-                Some(new_key)
+                Some((old_key, new_key))
             })
+            .collect::<Vec<(StrokeKey, StrokeKey)>>();
+
+        let new_selected = duplicated
+            .iter()
+            .map(|&(_, new_key)| new_key)
             .collect::<Vec<StrokeKey>>();
+        self.translate_strokes(&new_selected, offset);
+        self.translate_strokes_images(&new_selected, offset);
+
+        duplicated
+    }
+
+    /// Duplicate the selection and mirror the duplicates across the axis line at the given position,
+    /// in document coordinates.
+    ///
+    /// Both the original and the mirrored strokes are selected afterwards. Returns the keys of the mirrored strokes.
+    ///
+    /// The mirrored strokes then need to update their geometry and rendering.
+    pub(crate) fn mirror_duplicate_selection(
+        &mut self,
+        axis: Axis,
+        axis_pos: f64,
+    ) -> Vec<StrokeKey> {
+        let old_selected = self.selection_keys_as_rendered();
+        // the mirrored duplicates don't need an offset to make the duplication apparent
+        let new_selected = self.duplicate_selection(na::Vector2::zeros());
+        if new_selected.is_empty() {
+            return new_selected;
+        }
 
-        // Offsetting the new selected stroke to make the duplication apparent
-        self.translate_strokes(&new_selected, Stroke::IMPORT_OFFSET_DEFAULT);
-        self.translate_strokes_images(&new_selected, Stroke::IMPORT_OFFSET_DEFAULT);
+        let (scale, pivot) = match axis {
+            Axis::Vertical => (na::vector![-1.0, 1.0], na::vector![axis_pos, 0.0]),
+            Axis::Horizontal => (na::vector![1.0, -1.0], na::vector![0.0, axis_pos]),
+        };
+        self.scale_strokes_with_pivot(&new_selected, scale, pivot);
+        self.set_rendering_dirty_for_strokes(&new_selected);
+        self.set_selected_keys(&old_selected, true);
 
         new_selected
     }
+
+    /// Align each selected stroke to the edge or center of the reference stroke, which may or may not be selected.
+    ///
+    /// The reference stays in place. Returns the keys of the moved strokes.
+    ///
+    /// The strokes then need to update their geometry.
+    pub(crate) fn align_selection_to_key(
+        &mut self,
+        reference: StrokeKey,
+        alignment: Alignment,
+    ) -> Vec<StrokeKey> {
+        let Some(reference_bounds) = self
+            .stroke_components
+            .get(reference)
+            .map(|stroke| stroke.bounds())
+        else {
+            return vec![];
+        };
+        let selection = self
+            .selection_keys_as_rendered()
+            .into_iter()
+            .filter(|&key| key != reference)
+            .collect::<Vec<StrokeKey>>();
+
+        self.keyed_strokes_bounds(&selection)
+            .into_iter()
+            .filter_map(|(key, bounds)| {
+                let offset = alignment.offset(bounds, reference_bounds);
+                if offset == na::Vector2::zeros() {
+                    return None;
+                }
+                self.translate_strokes(&[key], offset);
+                self.translate_strokes_images(&[key], offset);
+                Some(key)
+            })
+            .collect()
+    }
+
+    /// Untangle the selected polygon shapes with self-intersecting outlines, which break up their fill.
+    ///
+    /// Other strokes are left untouched. Returns the keys of the repaired strokes.
+    ///
+    /// The strokes then need to update their geometry.
+    pub(crate) fn repair_selection_shapes(&mut self) -> Vec<StrokeKey> {
+        let self_intersecting = self
+            .selection_keys_as_rendered()
+            .into_iter()
+            .filter(|&key| {
+                matches!(
+                    self.stroke_components.get(key).map(|stroke| stroke.as_ref()),
+                    Some(Stroke::ShapeStroke(shapestroke))
+                        if matches!(&shapestroke.shape, Shape::Polygon(polygon) if polygon.self_intersects())
+                )
+            })
+            .collect::<Vec<StrokeKey>>();
+        if self_intersecting.is_empty() {
+            return vec![];
+        }
+
+        let stroke_components = Arc::make_mut(&mut self.stroke_components);
+        self_intersecting
+            .into_iter()
+            .filter(|&key| {
+                let Some(Stroke::ShapeStroke(shapestroke)) =
+                    stroke_components.get_mut(key).map(Arc::make_mut)
+                else {
+                    return false;
+                };
+                let Shape::Polygon(polygon) = &mut shapestroke.shape else {
+                    return false;
+                };
+                polygon.untangle()
+            })
+            .collect()
+    }
+
+    /// Join the selected brush strokes whose endpoints are within the maximum gap into continuous strokes,
+    /// e.g. to clean up fragmented handwriting.
+    ///
+    /// The closest endpoints are joined first. The joined stroke keeps the style of the stroke that was drawn earlier,
+    /// the other stroke is trashed. Returns the keys of the joined strokes, which stay selected.
+    ///
+    /// The joined strokes then need to update their geometry and rendering.
+    pub(crate) fn connect_selected_brushstrokes(&mut self, max_gap: f64) -> Vec<StrokeKey> {
+        let mut paths = self
+            .selection_keys_as_rendered()
+            .into_iter()
+            .filter_map(|key| match self.stroke_components.get(key)?.as_ref() {
+                Stroke::BrushStroke(brushstroke) => Some((key, brushstroke.path.clone())),
+                _ => None,
+            })
+            .collect::<Vec<(StrokeKey, PenPath)>>();
+        let mut joined = vec![];
+        let mut trashed = vec![];
+
+        while let Some((i, j, reverse_first, reverse_second)) =
+            closest_path_endpoints(&paths, max_gap)
+        {
+            // i < j, so removing j keeps the index of i
+            let (second_key, second) = paths.remove(j);
+            let (first_key, first) = &mut paths[i];
+            if reverse_first {
+                *first = first.reversed();
+            }
+            first.append(if reverse_second {
+                second.reversed()
+            } else {
+                second
+            });
+            joined.retain(|&key| key != second_key);
+            if !joined.contains(first_key) {
+                joined.push(*first_key);
+            }
+            trashed.push(second_key);
+        }
+
+        for (key, path) in paths {
+            if !joined.contains(&key) {
+                continue;
+            }
+            if let Some(Stroke::BrushStroke(brushstroke)) = self.get_stroke_mut(key) {
+                brushstroke.replace_path(path);
+            }
+        }
+        self.set_trashed_keys(&trashed, true);
+        joined
+    }
+
+    /// Smooth the paths of the selected brush strokes with a moving average over `radius` neighbouring elements,
+    /// see [PenPath::smoothed]. Shapes, text and images are untouched.
+    ///
+    /// Returns the keys of the smoothed strokes.
+    ///
+    /// The strokes then need to update their geometry and rendering.
+    pub(crate) fn smooth_selection(&mut self, radius: usize) -> Vec<StrokeKey> {
+        if radius == 0 {
+            return vec![];
+        }
+        let smoothed = self
+            .selection_keys_as_rendered()
+            .into_iter()
+            .filter(|&key| {
+                matches!(
+                    self.stroke_components
+                        .get(key)
+                        .map(|stroke| stroke.as_ref()),
+                    Some(Stroke::BrushStroke(_))
+                )
+            })
+            .collect::<Vec<StrokeKey>>();
+        for &key in smoothed.iter() {
+            if let Some(Stroke::BrushStroke(brushstroke)) = self.get_stroke_mut(key) {
+                brushstroke.replace_path(brushstroke.path.smoothed(radius));
+            }
+        }
+        smoothed
+    }
+
+    /// Arrange the selected strokes in a grid with the given number of columns, in chronological order.
+    ///
+    /// The grid starts at the top-left corner of the current selection bounds. All cells have the size of the largest
+    /// selected stroke and are separated by the spacing. The strokes keep their size, and are moved to the top-left corner of their cell.
+    /// Returns the arranged keys.
+    ///
+    /// The strokes then need to update their geometry.
+    pub(crate) fn arrange_selection_grid(
+        &mut self,
+        cols: usize,
+        spacing: na::Vector2<f64>,
+    ) -> Vec<StrokeKey> {
+        let selection = self.selection_keys_as_rendered();
+        let Some(selection_bounds) = self.bounds_for_strokes(&selection) else {
+            return vec![];
+        };
+        if cols == 0 {
+            return vec![];
+        }
+        let keyed_bounds = self.keyed_strokes_bounds(&selection);
+        let cell_extents = keyed_bounds
+            .iter()
+            .fold(na::Vector2::<f64>::zeros(), |acc, (_, bounds)| {
+                acc.sup(&bounds.extents())
+            });
+        let cell_step = cell_extents + spacing;
+
+        for (i, (key, bounds)) in keyed_bounds.iter().enumerate() {
+            let cell_pos = selection_bounds.mins.coords
+                + na::vector![(i % cols) as f64, (i / cols) as f64].component_mul(&cell_step);
+            let offset = cell_pos - bounds.mins.coords;
+            self.translate_strokes(&[*key], offset);
+            self.translate_strokes_images(&[*key], offset);
+        }
+
+        selection
+    }
+
+    /// Reflow the selected strokes like words into lines that wrap at the line width, in chronological order.
+    ///
+    /// The lines start at the top-left corner of the current selection bounds, every stroke is vertically centered
+    /// in its line. A stroke wider than the line width gets a line of its own. Returns the reflowed keys,
+    /// empty if fewer than two strokes are selected or the line width or height are not positive.
+    ///
+    /// The strokes then need to update their geometry.
+    pub(crate) fn reflow_selection(&mut self, line_width: f64, line_height: f64) -> Vec<StrokeKey> {
+        if !line_width.is_finite()
+            || !line_height.is_finite()
+            || line_width <= 0.0
+            || line_height <= 0.0
+        {
+            return vec![];
+        }
+        let selection = self.selection_keys_sorted_chrono_t();
+        if selection.len() < 2 {
+            return vec![];
+        }
+        let Some(selection_bounds) = self.bounds_for_strokes(&selection) else {
+            return vec![];
+        };
+        let line_start = selection_bounds.mins[0];
+        let word_spacing = line_height * REFLOW_WORD_SPACING;
+        let mut pos = selection_bounds.mins.coords;
+
+        for (key, bounds) in self.keyed_strokes_bounds(&selection) {
+            let extents = bounds.extents();
+            if pos[0] > line_start && pos[0] + extents[0] > line_start + line_width {
+                pos = na::vector![line_start, pos[1] + line_height];
+            }
+            let target = na::vector![pos[0], pos[1] + (line_height - extents[1]) * 0.5];
+            let offset = target - bounds.mins.coords;
+            self.translate_strokes(&[key], offset);
+            self.translate_strokes_images(&[key], offset);
+            pos[0] += extents[0] + word_spacing;
+        }
+
+        selection
+    }
+
+    /// Move the selected strokes radially away from the center of the selection, so that their bounds
+    /// are at least the spacing apart.
+    ///
+    /// The strokes closest to the center are placed first and stay where they are when they don't overlap,
+    /// the others are moved outward step by step until they don't overlap any placed stroke.
+    /// Strokes right at the center are spread out by the golden angle. Returns the moved keys.
+    ///
+    /// The strokes then need to update their geometry.
+    pub(crate) fn explode_selection(&mut self, spacing: f64) -> Vec<StrokeKey> {
+        if !spacing.is_finite() || spacing < 0.0 {
+            return vec![];
+        }
+        let selection = self.selection_keys_as_rendered();
+        let Some(selection_bounds) = self.bounds_for_strokes(&selection) else {
+            return vec![];
+        };
+        let centroid = selection_bounds.center().coords;
+        let step = spacing.max(1.0);
+        let mut keyed_bounds = self.keyed_strokes_bounds(&selection);
+        keyed_bounds.sort_by(|(_, first), (_, second)| {
+            (first.center().coords - centroid)
+                .norm()
+                .total_cmp(&(second.center().coords - centroid).norm())
+        });
+
+        let mut placed: Vec<Aabb> = Vec::with_capacity(keyed_bounds.len());
+        let mut moved = vec![];
+        for (i, (key, bounds)) in keyed_bounds.into_iter().enumerate() {
+            let direction = (bounds.center().coords - centroid)
+                .try_normalize(f64::EPSILON)
+                .unwrap_or_else(|| {
+                    let angle = i as f64 * std::f64::consts::PI * (3.0 - 5.0_f64.sqrt());
+                    na::vector![angle.cos(), angle.sin()]
+                });
+            let mut offset = na::Vector2::<f64>::zeros();
+            for _ in 0..EXPLODE_SELECTION_MAX_STEPS {
+                let candidate = bounds.translate(offset).loosened(spacing * 0.5);
+                if !placed.iter().any(|other| other.intersects(&candidate)) {
+                    break;
+                }
+                offset += direction * step;
+            }
+            placed.push(bounds.translate(offset).loosened(spacing * 0.5));
+            if offset != na::Vector2::zeros() {
+                self.translate_strokes(&[key], offset);
+                self.translate_strokes_images(&[key], offset);
+                moved.push(key);
+            }
+        }
+
+        moved
+    }
+
+    /// Rotate the selected strokes whose overall direction is within the threshold angle (in radians)
+    /// of horizontal or vertical, so that they are exactly axis-aligned.
+    ///
+    /// Only lines, arrows and brush strokes have a direction, other strokes are left unchanged.
+    /// Returns the keys of the straightened strokes.
+    ///
+    /// The strokes then need to update their geometry.
+    pub(crate) fn straighten_selection(&mut self, angle_threshold: f64) -> Vec<StrokeKey> {
+        const QUARTER_TURN: f64 = std::f64::consts::FRAC_PI_2;
+
+        let straightening = self
+            .selection_keys_as_rendered()
+            .into_iter()
+            .filter_map(|key| {
+                let (start, end) = stroke_direction(self.stroke_components.get(key)?)?;
+                let direction = end - start;
+                if direction.norm() == 0.0 {
+                    return None;
+                }
+                let angle = direction[1].atan2(direction[0]);
+                let deviation = angle - (angle / QUARTER_TURN).round() * QUARTER_TURN;
+                if deviation == 0.0 || deviation.abs() > angle_threshold {
+                    return None;
+                }
+                Some((key, deviation, na::Point2::from((start + end) * 0.5)))
+            })
+            .collect::<Vec<(StrokeKey, f64, na::Point2<f64>)>>();
+
+        straightening
+            .into_iter()
+            .map(|(key, deviation, center)| {
+                self.rotate_strokes(&[key], -deviation, center);
+                self.rotate_strokes_images(&[key], -deviation, center);
+                key
+            })
+            .collect()
+    }
+
+    /// Resize the selection to the new extents, while the given anchor of the selection bounds stays fixed.
+    ///
+    /// The scale is clamped so that no selected stroke shrinks below `min_extent` on either axis,
+    /// a `min_extent` of zero allows unrestricted scaling.
+    /// Pinned strokes keep their size and are only moved along with the group, keeping their center at its relative position.
+    ///
+    /// Returns the new selection bounds, None if no strokes are selected.
+    ///
+    /// The strokes then need to update their geometry and the transform needs to be committed.
+    pub(crate) fn resize_selection(
+        &mut self,
+        new_extents: na::Vector2<f64>,
+        anchor: ResizeAnchor,
+        min_extent: f64,
+        pinned: &HashSet<StrokeKey>,
+    ) -> Option<Aabb> {
+        let resized =
+            self.resize_selection_geometry_only(new_extents, anchor, min_extent, pinned)?;
+        self.scale_strokes_images_with_pivot(&resized.scaled_keys, resized.scale, resized.pivot);
+        for (key, offset) in resized.pinned_offsets {
+            self.translate_strokes_images(&[key], offset);
+        }
+        Some(resized.bounds)
+    }
+
+    /// Resize the selection like [StrokeStore::resize_selection], but only transform the stroke geometry.
+    ///
+    /// The render components are left untouched, so this doesn't need a rendering context.
+    /// Returns how the geometry was transformed, None if no strokes are selected.
+    pub(crate) fn resize_selection_geometry_only(
+        &mut self,
+        new_extents: na::Vector2<f64>,
+        anchor: ResizeAnchor,
+        min_extent: f64,
+        pinned: &HashSet<StrokeKey>,
+    ) -> Option<ResizedSelection> {
+        let selection = self.selection_keys_unordered();
+        let (selection_bounds, scale, pivot) =
+            self.selection_resize_params(&selection, new_extents, anchor, min_extent)?;
+        let (pinned_keys, scaled_keys): (Vec<StrokeKey>, Vec<StrokeKey>) = selection
+            .iter()
+            .copied()
+            .partition(|key| pinned.contains(key));
+
+        self.scale_strokes_with_pivot(&scaled_keys, scale, pivot);
+        let pinned_offsets = self
+            .keyed_strokes_bounds(&pinned_keys)
+            .into_iter()
+            .map(|(key, bounds)| {
+                let center = bounds.center().coords;
+                let offset = pivot + (center - pivot).component_mul(&scale) - center;
+                self.translate_strokes(&[key], offset);
+                (key, offset)
+            })
+            .collect::<Vec<(StrokeKey, na::Vector2<f64>)>>();
+        self.set_transform_dirty_for_strokes(&selection);
+
+        let bounds = if pinned_keys.is_empty() {
+            resized_bounds(selection_bounds, scale, pivot)
+        } else {
+            self.bounds_for_strokes(&selection)?
+        };
+        Some(ResizedSelection {
+            bounds,
+            scaled_keys,
+            scale,
+            pivot,
+            pinned_offsets,
+        })
+    }
+
+    /// The current selection bounds, and the scale and pivot needed to resize the selection to the new extents.
+    fn selection_resize_params(
+        &self,
+        selection: &[StrokeKey],
+        new_extents: na::Vector2<f64>,
+        anchor: ResizeAnchor,
+        min_extent: f64,
+    ) -> Option<(Aabb, na::Vector2<f64>, na::Vector2<f64>)> {
+        if !new_extents.iter().all(|v| v.is_finite()) {
+            warn!("Skipping resizing the selection, new extents {new_extents:?} are not finite.");
+            return None;
+        }
+        let selection_bounds = self.bounds_for_strokes(selection)?;
+        let extents = selection_bounds.extents();
+        let pivot = anchor.pos(selection_bounds);
+        // Degenerate axes can't be scaled, so they are left untouched
+        let mut scale = na::vector![
+            if extents[0] > 0.0 {
+                new_extents[0] / extents[0]
+            } else {
+                1.0
+            },
+            if extents[1] > 0.0 {
+                new_extents[1] / extents[1]
+            } else {
+                1.0
+            }
+        ];
+        if min_extent > 0.0 {
+            for stroke_extents in self
+                .strokes_bounds(selection)
+                .into_iter()
+                .map(|bounds| bounds.extents())
+            {
+                for (axis_scale, &stroke_extent) in scale.iter_mut().zip(stroke_extents.iter()) {
+                    if stroke_extent > 0.0 && *axis_scale > 0.0 {
+                        // strokes that are already below the minimum don't shrink any further
+                        *axis_scale = axis_scale.max((min_extent / stroke_extent).min(1.0));
+                    }
+                }
+            }
+        }
+
+        Some((selection_bounds, scale, pivot))
+    }
+
+    /// Rotate the selection by the given angle (in radians) around the center of the selection bounds.
+    ///
+    /// Upright strokes keep their orientation and are only moved along with the group, keeping their center at its relative position.
+    ///
+    /// Returns the new selection bounds, None if no strokes are selected.
+    ///
+    /// The strokes then need to update their geometry and the transform needs to be committed.
+    pub(crate) fn rotate_selection(
+        &mut self,
+        angle: f64,
+        keep_upright: &HashSet<StrokeKey>,
+    ) -> Option<Aabb> {
+        let selection = self.selection_keys_unordered();
+        let center = self.bounds_for_strokes(&selection)?.center();
+        let (upright_keys, rotated_keys): (Vec<StrokeKey>, Vec<StrokeKey>) = selection
+            .iter()
+            .copied()
+            .partition(|key| keep_upright.contains(key));
+
+        self.rotate_strokes(&rotated_keys, angle, center);
+        self.rotate_strokes_images(&rotated_keys, angle, center);
+        let rotation = na::Rotation2::new(angle);
+        for (key, bounds) in self.keyed_strokes_bounds(&upright_keys) {
+            let stroke_center = bounds.center();
+            let offset = (center + rotation * (stroke_center - center)) - stroke_center;
+            self.translate_strokes(&[key], offset);
+            self.translate_strokes_images(&[key], offset);
+        }
+        self.set_transform_dirty_for_strokes(&selection);
+
+        self.bounds_for_strokes(&selection)
+    }
+
+    /// Estimate the area that is covered by the selected strokes, in document coordinates.
+    ///
+    /// Sums up the areas of the stroke hitboxes, so overlapping hitboxes are counted multiple times.
+    pub(crate) fn selection_ink_area(&self) -> f64 {
+        self.selection_keys_unordered()
+            .into_iter()
+            .filter_map(|key| self.stroke_components.get(key))
+            .flat_map(|stroke| stroke.hitboxes())
+            .map(|hitbox| hitbox.volume())
+            .sum()
+    }
+
+    /// Check whether any of the selected strokes' bounds intersect the bounds of the given stroke.
+    ///
+    /// Always false if the given stroke is selected, trashed or does not exist.
+    pub(crate) fn selection_overlaps_stroke(&self, key: StrokeKey) -> bool {
+        if self.trashed(key).unwrap_or(true) || self.selected(key).unwrap_or(false) {
+            return false;
+        }
+        let Some(stroke_bounds) = self.stroke_components.get(key).map(|s| s.bounds()) else {
+            return false;
+        };
+
+        self.selection_keys_unordered()
+            .into_iter()
+            .filter_map(|selected_key| self.stroke_components.get(selected_key))
+            .any(|selected| selected.bounds().intersects(&stroke_bounds))
+    }
+
+    /// The keys of the non-selected strokes whose bounds intersect the selection bounds, in the order they are rendered.
+    ///
+    /// Trashed strokes are excluded. Empty if nothing is selected.
+    pub(crate) fn strokes_overlapping_selection(&self) -> Vec<StrokeKey> {
+        let Some(selection_bounds) = self.selection_bounds() else {
+            return vec![];
+        };
+        self.stroke_keys_as_rendered_intersecting_bounds(selection_bounds)
+            .into_iter()
+            .filter(|&key| !self.selected(key).unwrap_or(false))
+            .collect()
+    }
+
+    /// Compute the offset that would align the selection edges to the edges of nearby non-selected strokes.
+    ///
+    /// Only edges within the given threshold are considered, each axis snaps to the closest one.
+    /// Zero if no candidate is within the threshold.
+    pub(crate) fn compute_snap_offset(&self, threshold: f64) -> na::Vector2<f64> {
+        let Some(selection_bounds) = self.selection_bounds() else {
+            return na::Vector2::zeros();
+        };
+        let mut snap_offset: [Option<f64>; 2] = [None, None];
+
+        for key in self
+            .key_tree
+            .keys_intersecting_bounds(selection_bounds.loosened(threshold))
+        {
+            if self.trashed(key).unwrap_or(true) || self.selected(key).unwrap_or(false) {
+                continue;
+            }
+            let Some(bounds) = self.stroke_components.get(key).map(|s| s.bounds()) else {
+                continue;
+            };
+
+            for (axis, axis_offset) in snap_offset.iter_mut().enumerate() {
+                for selection_edge in [selection_bounds.mins[axis], selection_bounds.maxs[axis]] {
+                    for edge in [bounds.mins[axis], bounds.maxs[axis]] {
+                        let offset = edge - selection_edge;
+
+                        if offset.abs() <= threshold
+                            && axis_offset.map_or(true, |current| offset.abs() < current.abs())
+                        {
+                            *axis_offset = Some(offset);
+                        }
+                    }
+                }
+            }
+        }
+
+        na::Vector2::new(snap_offset[0].unwrap_or(0.0), snap_offset[1].unwrap_or(0.0))
+    }
+
+    /// Compute the guide lines where the edges or the center of the selection are within the threshold
+    /// of the edges or the center of non-selected strokes.
+    ///
+    /// The guides are placed at the positions of the other strokes, which is where the selection would align.
+    /// Guides at the same position are merged. Empty if nothing is selected.
+    pub(crate) fn compute_alignment_guides(&self, threshold: f64) -> Vec<Guide> {
+        let Some(selection_bounds) = self.selection_bounds() else {
+            return vec![];
+        };
+        let mut guides: Vec<Guide> = vec![];
+
+        for key in self.stroke_keys_as_rendered() {
+            if self.selected(key).unwrap_or(false) {
+                continue;
+            }
+            let Some(bounds) = self.stroke_components.get(key).map(|s| s.bounds()) else {
+                continue;
+            };
+
+            for (axis_idx, axis) in [(0, Axis::Vertical), (1, Axis::Horizontal)] {
+                // guides extend along the other axis
+                let start = selection_bounds.mins[1 - axis_idx].min(bounds.mins[1 - axis_idx]);
+                let end = selection_bounds.maxs[1 - axis_idx].max(bounds.maxs[1 - axis_idx]);
+
+                for selection_line in alignment_lines(selection_bounds, axis_idx) {
+                    for pos in alignment_lines(bounds, axis_idx) {
+                        if (pos - selection_line).abs() > threshold {
+                            continue;
+                        }
+                        match guides
+                            .iter_mut()
+                            .find(|guide| guide.axis == axis && guide.pos == pos)
+                        {
+                            Some(guide) => {
+                                guide.start = guide.start.min(start);
+                                guide.end = guide.end.max(end);
+                            }
+                            None => guides.push(Guide {
+                                axis,
+                                pos,
+                                start,
+                                end,
+                            }),
+                        }
+                    }
+                }
+            }
+        }
+
+        guides
+    }
+
+    /// The accumulated transforms of the selected strokes since they were selected, in the order they are rendered.
+    ///
+    /// Transforms are not deferred: translating, resizing and rotating transform the stroke geometry right away,
+    /// which stays the source of truth for the bounds, hit testing and exports. The matrices only describe
+    /// what was applied since selecting, so that a renderer can transform content it generated earlier
+    /// until that content is regenerated.
+    pub(crate) fn selection_transforms(&self) -> Vec<(StrokeKey, na::Matrix3<f64>)> {
+        self.selection_keys_as_rendered()
+            .into_iter()
+            .filter_map(|key| {
+                let selection_comp = self.selection_components.get(key)?;
+                Some((key, selection_comp.transform.affine.to_homogeneous()))
+            })
+            .collect()
+    }
+
+    /// Reset the accumulated transforms of the selected strokes to identity.
+    ///
+    /// There is nothing to bake into the geometry, because it already carries the transforms
+    /// (see [StrokeStore::selection_transforms]). Returns the keys of the strokes that had a non-identity transform.
+    ///
+    /// The strokes then need to update their rendering.
+    pub(crate) fn reset_selection_transforms(&mut self) -> Vec<StrokeKey> {
+        let reset = self
+            .selection_keys_unordered()
+            .into_iter()
+            .filter(|&key| {
+                self.selection_components
+                    .get(key)
+                    .map_or(false, |selection_comp| {
+                        selection_comp.transform.affine != na::Affine2::identity()
+                    })
+            })
+            .collect::<Vec<StrokeKey>>();
+        for &key in &reset {
+            self.update_selection_transform(key, |transform| *transform = Transform::default());
+        }
+        reset
+    }
+
+    /// Select all other strokes whose bounds intersect the bounds of the reference stroke.
+    ///
+    /// The reference stroke itself is not selected. Returns the newly selected keys.
+    pub(crate) fn select_overlapping_key(&mut self, reference: StrokeKey) -> Vec<StrokeKey> {
+        let Some(reference_bounds) = self.stroke_components.get(reference).map(|s| s.bounds())
+        else {
+            return vec![];
+        };
+        let overlapping = self
+            .stroke_keys_as_rendered_intersecting_bounds(reference_bounds)
+            .into_iter()
+            .filter(|&key| key != reference)
+            .collect::<Vec<StrokeKey>>();
+        self.set_selected_keys(&overlapping, true);
+        overlapping
+    }
+
+    /// Grow the selection by the strokes whose bounds intersect the bounds of a selected stroke, repeatedly,
+    /// until the entire connected group of overlapping strokes is selected.
+    ///
+    /// Stops after a maximum number of rounds, to guard against pathological documents. Returns the newly selected keys.
+    pub(crate) fn expand_selection_to_touching(&mut self) -> Vec<StrokeKey> {
+        let mut frontier = self.selection_keys_unordered();
+        let mut expanded = vec![];
+
+        for _ in 0..EXPAND_SELECTION_MAX_ITERATIONS {
+            let mut touching = self
+                .strokes_bounds(&frontier)
+                .into_iter()
+                .flat_map(|bounds| self.stroke_keys_as_rendered_intersecting_bounds(bounds))
+                .filter(|&key| !self.selected(key).unwrap_or(true))
+                .collect::<Vec<StrokeKey>>();
+            touching.sort_unstable();
+            touching.dedup();
+            if touching.is_empty() {
+                break;
+            }
+            self.set_selected_keys(&touching, true);
+            expanded.extend_from_slice(&touching);
+            frontier = touching;
+        }
+
+        expanded
+    }
+
+    /// Select all strokes whose bounds don't intersect the given region, e.g. strokes that ended up far outside the document.
+    ///
+    /// Returns the newly selected keys.
+    pub(crate) fn select_strokes_outside(&mut self, region: Aabb) -> Vec<StrokeKey> {
+        let outside = self
+            .stroke_keys_as_rendered()
+            .into_iter()
+            .filter(|&key| {
+                self.stroke_components
+                    .get(key)
+                    .map(|stroke| !stroke.bounds().intersects(&region))
+                    .unwrap_or(false)
+            })
+            .collect::<Vec<StrokeKey>>();
+        self.set_selected_keys(&outside, true);
+        outside
+    }
+
+    /// Select all strokes whose largest bounds extent lies within the given range, both limits inclusive.
+    ///
+    /// A missing limit leaves the range open on that side,
+    /// e.g. only a maximum selects tiny specks and only a minimum selects huge stray strokes.
+    /// Returns the newly selected keys.
+    pub(crate) fn select_strokes_by_size(
+        &mut self,
+        min_extent: Option<f64>,
+        max_extent: Option<f64>,
+    ) -> Vec<StrokeKey> {
+        let in_range = self
+            .stroke_keys_as_rendered()
+            .into_iter()
+            .filter(|&key| {
+                let Some(stroke) = self.stroke_components.get(key) else {
+                    return false;
+                };
+                let extent = stroke.bounds().extents().max();
+                min_extent.map_or(true, |min| extent >= min)
+                    && max_extent.map_or(true, |max| extent <= max)
+            })
+            .collect::<Vec<StrokeKey>>();
+        self.set_selected_keys(&in_range, true);
+        in_range
+    }
+
+    /// Select all strokes whose bounds width to height ratio lies within the given range, both limits inclusive.
+    ///
+    /// Strokes without height but with width have an infinite ratio, so they are only selected by an infinite maximum.
+    /// Strokes without any extents are never selected. Returns the newly selected keys.
+    pub(crate) fn select_by_aspect_ratio(&mut self, min: f64, max: f64) -> Vec<StrokeKey> {
+        let in_range = self
+            .stroke_keys_as_rendered()
+            .into_iter()
+            .filter(|&key| {
+                let Some(stroke) = self.stroke_components.get(key) else {
+                    return false;
+                };
+                let extents = stroke.bounds().extents();
+                let ratio = if extents[1] > 0.0 {
+                    extents[0] / extents[1]
+                } else if extents[0] > 0.0 {
+                    f64::INFINITY
+                } else {
+                    return false;
+                };
+                ratio >= min && ratio <= max
+            })
+            .collect::<Vec<StrokeKey>>();
+        self.set_selected_keys(&in_range, true);
+        in_range
+    }
+
+    /// The stroke colors of all strokes, with near-identical colors grouped together,
+    /// and the number of strokes with each color.
+    ///
+    /// Sorted by descending count. Images and trashed strokes are excluded.
+    pub(crate) fn color_histogram(&self) -> Vec<(Color, usize)> {
+        let mut histogram: Vec<(Color, usize)> = vec![];
+        for key in self.stroke_keys_as_rendered() {
+            let Some(color) = self
+                .stroke_components
+                .get(key)
+                .and_then(|stroke| StrokeStyleProps::from_stroke(stroke))
+                .and_then(|props| props.stroke_color)
+            else {
+                continue;
+            };
+            match histogram
+                .iter_mut()
+                .find(|(entry_color, _)| palette_colors_match(*entry_color, color))
+            {
+                Some((_, count)) => *count += 1,
+                None => histogram.push((color, 1)),
+            }
+        }
+        histogram.sort_by(|(_, first), (_, second)| second.cmp(first));
+        histogram
+    }
+
+    /// Select all strokes whose stroke color is near-identical to the given palette entry color.
+    ///
+    /// Images and trashed strokes are never selected. Returns the newly selected keys.
+    pub(crate) fn select_by_palette_entry(&mut self, color: Color) -> Vec<StrokeKey> {
+        let matching = self
+            .stroke_keys_as_rendered()
+            .into_iter()
+            .filter(|&key| {
+                self.stroke_components
+                    .get(key)
+                    .and_then(|stroke| StrokeStyleProps::from_stroke(stroke))
+                    .and_then(|props| props.stroke_color)
+                    .map_or(false, |stroke_color| {
+                        palette_colors_match(stroke_color, color)
+                    })
+            })
+            .collect::<Vec<StrokeKey>>();
+        self.set_selected_keys(&matching, true);
+        matching
+    }
+
+    /// The text of the selected strokes in reading order, top-to-bottom and left-to-right.
+    ///
+    /// Strokes whose vertical center lies within the vertical range of a line are placed on the same line.
+    /// The texts of a line are separated by spaces, lines by newlines.
+    /// Only text strokes carry text, None if no selected stroke has any text.
+    pub(crate) fn selection_text(&self) -> Option<String> {
+        let mut texts = self
+            .selection_keys_unordered()
+            .into_iter()
+            .filter_map(|key| match self.stroke_components.get(key)?.as_ref() {
+                Stroke::TextStroke(textstroke) if !textstroke.text.trim().is_empty() => {
+                    Some((textstroke.bounds(), textstroke.text.trim()))
+                }
+                _ => None,
+            })
+            .collect::<Vec<(Aabb, &str)>>();
+        if texts.is_empty() {
+            return None;
+        }
+        texts.sort_by(|(first, _), (second, _)| first.center()[1].total_cmp(&second.center()[1]));
+
+        // (the vertical range, the texts) of each line
+        let mut lines: Vec<((f64, f64), Vec<(Aabb, &str)>)> = vec![];
+        for (bounds, text) in texts {
+            match lines.last_mut() {
+                Some(((top, bottom), line))
+                    if bounds.center()[1] >= *top && bounds.center()[1] <= *bottom =>
+                {
+                    *top = top.min(bounds.mins[1]);
+                    *bottom = bottom.max(bounds.maxs[1]);
+                    line.push((bounds, text));
+                }
+                _ => lines.push(((bounds.mins[1], bounds.maxs[1]), vec![(bounds, text)])),
+            }
+        }
+
+        Some(
+            lines
+                .into_iter()
+                .map(|(_, mut line)| {
+                    line.sort_by(|(first, _), (second, _)| {
+                        first.mins[0].total_cmp(&second.mins[0])
+                    });
+                    line.into_iter()
+                        .map(|(_, text)| text)
+                        .collect::<Vec<&str>>()
+                        .join(" ")
+                })
+                .collect::<Vec<String>>()
+                .join("\n"),
+        )
+    }
+
+    /// Move the selection to the neighbouring stroke in chronological order, wrapping around at the ends.
+    ///
+    /// When several strokes are selected, steps from the last selected stroke when moving forward
+    /// and from the first when moving backward. When nothing is selected, the first or last stroke is selected.
+    /// Trashed and hidden strokes are skipped. Returns the newly selected key.
+    pub(crate) fn select_neighbour_stroke(&mut self, forward: bool) -> Option<StrokeKey> {
+        let keys = self
+            .stroke_keys_as_rendered()
+            .into_iter()
+            .filter(|&key| self.rendered(key))
+            .collect::<Vec<StrokeKey>>();
+        if keys.is_empty() {
+            return None;
+        }
+        let selected_positions = keys
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| self.selected(**key).unwrap_or(false))
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>();
+        let neighbour = match (
+            forward,
+            selected_positions.first(),
+            selected_positions.last(),
+        ) {
+            (true, _, Some(&last)) => keys[(last + 1) % keys.len()],
+            (false, Some(&first), _) => keys[(first + keys.len() - 1) % keys.len()],
+            (true, ..) => keys[0],
+            (false, ..) => keys[keys.len() - 1],
+        };
+        // hidden strokes can still be selected, so deselect the entire selection
+        self.set_selected_keys(&self.selection_keys_unordered(), false);
+        self.set_selected(neighbour, true);
+        Some(neighbour)
+    }
+}
+
+/// The start and end position of the overall direction of the stroke.
+///
+/// None for strokes that don't have a meaningful direction.
+fn stroke_direction(stroke: &Stroke) -> Option<(na::Vector2<f64>, na::Vector2<f64>)> {
+    match stroke {
+        Stroke::BrushStroke(brushstroke) => Some((
+            brushstroke.path.start.pos,
+            brushstroke.path.segments.last()?.end().pos,
+        )),
+        Stroke::ShapeStroke(shapestroke) => match &shapestroke.shape {
+            Shape::Line(line) => Some((line.start, line.end)),
+            Shape::Arrow(arrow) => Some((arrow.start, arrow.tip)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The bounds scaled by the given scale around the pivot.
+fn resized_bounds(bounds: Aabb, scale: na::Vector2<f64>, pivot: na::Vector2<f64>) -> Aabb {
+    bounds
+        .translate(-pivot)
+        .scale_non_uniform(scale)
+        .translate(pivot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SelectionComponent, SelectionDelta};
+    use crate::store::render_comp::RenderCompState;
+    use crate::store::ResizeAnchor;
+    use crate::strokes::{BrushStroke, ShapeStroke, Stroke};
+    use crate::StrokeStore;
+    use p2d::bounding_volume::BoundingVolume;
+    use rnote_compose::penpath::Element;
+    use rnote_compose::shapes::{Line, Shape, Shapeable};
+    use rnote_compose::Style;
+    use std::collections::HashSet;
+
+    /// Removes the fields that were added with versioning, turning the value into a pre-versioning document.
+    fn strip_versioned_fields(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                if map.contains_key("selected") {
+                    map.remove("version");
+                }
+                map.values_mut().for_each(strip_versioned_fields);
+            }
+            serde_json::Value::Array(values) => values.iter_mut().for_each(strip_versioned_fields),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn deserialize_unversioned_selection_component() {
+        let selection_comp: SelectionComponent =
+            serde_json::from_str(r#"{"selected": true}"#).unwrap();
+
+        assert!(selection_comp.selected);
+        assert_eq!(selection_comp.version, 0);
+    }
+
+    #[test]
+    fn load_unversioned_store_keeps_selection() {
+        let mut store = StrokeStore::default();
+        let selected_key = store.insert_stroke(
+            Stroke::BrushStroke(BrushStroke::new(
+                Element::new(na::vector![0.0, 0.0], 1.0),
+                Style::default(),
+            )),
+            None,
+        );
+        store.insert_stroke(
+            Stroke::BrushStroke(BrushStroke::new(
+                Element::new(na::vector![10.0, 10.0], 1.0),
+                Style::default(),
+            )),
+            None,
+        );
+        store.set_selected(selected_key, true);
+
+        let mut value = serde_json::to_value(&store).unwrap();
+        strip_versioned_fields(&mut value);
+        let loaded: StrokeStore = serde_json::from_value(value).unwrap();
+
+        assert_eq!(loaded.selection_keys_unordered(), vec![selected_key]);
+        assert_eq!(loaded.selected(selected_key), Some(true));
+    }
+
+    #[test]
+    fn selection_ignores_key_of_reused_slot() {
+        let mut store = StrokeStore::default();
+        let removed_key = store.insert_stroke(
+            Stroke::BrushStroke(BrushStroke::new(
+                Element::new(na::vector![0.0, 0.0], 1.0),
+                Style::default(),
+            )),
+            None,
+        );
+        store.set_selected(removed_key, true);
+        store.remove_stroke(removed_key);
+        // the slotmap hands out the slot of the removed stroke again, with a new version
+        let reused_key = store.insert_stroke(
+            Stroke::BrushStroke(BrushStroke::new(
+                Element::new(na::vector![10.0, 10.0], 1.0),
+                Style::default(),
+            )),
+            None,
+        );
+
+        assert_ne!(removed_key, reused_key);
+        assert_eq!(store.selected(reused_key), Some(false));
+        assert!(store.selection_keys_unordered().is_empty());
+
+        store.set_selected(reused_key, true);
+        assert_eq!(store.selection_keys_as_rendered(), vec![reused_key]);
+    }
+
+    #[test]
+    fn resize_selection_geometry_only_leaves_render_components_untouched() {
+        let mut store = StrokeStore::default();
+        let key = store.insert_stroke(
+            Stroke::ShapeStroke(ShapeStroke::new(
+                Shape::Line(Line::new(na::vector![0.0, 0.0], na::vector![100.0, 50.0])),
+                Style::default(),
+            )),
+            None,
+        );
+        store.set_selected(key, true);
+        store.render_components.get_mut(key).unwrap().state = RenderCompState::Complete;
+        let bounds = store.selection_bounds().unwrap();
+
+        let resized = store
+            .resize_selection_geometry_only(
+                bounds.extents() * 2.0,
+                ResizeAnchor::TopLeft,
+                0.0,
+                &HashSet::new(),
+            )
+            .unwrap();
+
+        approx::assert_relative_eq!(resized.bounds.mins, bounds.mins);
+        approx::assert_relative_eq!(resized.bounds.extents(), bounds.extents() * 2.0);
+        let render_comp = store.render_components.get(key).unwrap();
+        assert_eq!(render_comp.state, RenderCompState::Complete);
+        assert!(render_comp.images.is_empty());
+        assert_eq!(store.take_transform_dirty_keys(), vec![key]);
+    }
+
+    #[test]
+    fn resize_selection_geometry_only_without_selection() {
+        let mut store = StrokeStore::default();
+        store.insert_stroke(
+            Stroke::ShapeStroke(ShapeStroke::new(
+                Shape::Line(Line::new(na::vector![0.0, 0.0], na::vector![100.0, 50.0])),
+                Style::default(),
+            )),
+            None,
+        );
+
+        assert!(store
+            .resize_selection_geometry_only(
+                na::vector![200.0, 100.0],
+                ResizeAnchor::TopLeft,
+                0.0,
+                &HashSet::new(),
+            )
+            .is_none());
+        assert!(store.take_transform_dirty_keys().is_empty());
+    }
+
+    #[test]
+    fn selection_delta_since_empty_snapshot_reports_added_keys() {
+        let mut store = StrokeStore::default();
+        let key = store.insert_stroke(
+            Stroke::ShapeStroke(ShapeStroke::new(
+                Shape::Line(Line::new(na::vector![0.0, 0.0], na::vector![100.0, 0.0])),
+                Style::default(),
+            )),
+            None,
+        );
+        let snapshot = store.selection_snapshot();
+
+        assert!(store.selection_delta_since(&snapshot).is_empty());
+        store.set_selected(key, true);
+        assert_eq!(
+            store.selection_delta_since(&snapshot),
+            SelectionDelta {
+                added: vec![key],
+                removed: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn selection_delta_since_snapshot_reports_trashed_keys_as_removed() {
+        let mut store = StrokeStore::default();
+        let kept_key = store.insert_stroke(
+            Stroke::ShapeStroke(ShapeStroke::new(
+                Shape::Line(Line::new(na::vector![0.0, 0.0], na::vector![100.0, 0.0])),
+                Style::default(),
+            )),
+            None,
+        );
+        let trashed_key = store.insert_stroke(
+            Stroke::ShapeStroke(ShapeStroke::new(
+                Shape::Line(Line::new(na::vector![0.0, 50.0], na::vector![100.0, 50.0])),
+                Style::default(),
+            )),
+            None,
+        );
+        store.set_selected_keys(&[kept_key, trashed_key], true);
+        let snapshot = store.selection_snapshot();
+
+        // trashed strokes stay selected in their component, but are no longer part of the selection
+        store.set_trashed(trashed_key, true);
+        assert_eq!(
+            store.selection_delta_since(&snapshot),
+            SelectionDelta {
+                added: vec![],
+                removed: vec![trashed_key],
+            }
+        );
+    }
+
+    #[test]
+    fn select_bookmark_skips_trashed_strokes() {
+        let mut store = StrokeStore::default();
+        let kept_key = store.insert_stroke(
+            Stroke::ShapeStroke(ShapeStroke::new(
+                Shape::Line(Line::new(na::vector![0.0, 0.0], na::vector![100.0, 0.0])),
+                Style::default(),
+            )),
+            None,
+        );
+        let trashed_key = store.insert_stroke(
+            Stroke::ShapeStroke(ShapeStroke::new(
+                Shape::Line(Line::new(na::vector![0.0, 50.0], na::vector![100.0, 50.0])),
+                Style::default(),
+            )),
+            None,
+        );
+        store.set_selected_keys(&[kept_key, trashed_key], true);
+        store.bookmark_selection("intro");
+        store.set_selected_keys(&[kept_key, trashed_key], false);
+        store.set_trashed(trashed_key, true);
+
+        assert_eq!(store.select_bookmark("intro"), Some(vec![kept_key]));
+        assert_eq!(store.selection_keys_unordered(), vec![kept_key]);
+        assert_eq!(store.select_bookmark("missing"), None);
+    }
+
+    #[test]
+    fn select_bookmark_of_empty_selection_clears_the_selection() {
+        let mut store = StrokeStore::default();
+        let key = store.insert_stroke(
+            Stroke::ShapeStroke(ShapeStroke::new(
+                Shape::Line(Line::new(na::vector![0.0, 0.0], na::vector![100.0, 0.0])),
+                Style::default(),
+            )),
+            None,
+        );
+        store.bookmark_selection("empty");
+        store.set_selected(key, true);
+
+        assert_eq!(store.select_bookmark("empty"), Some(vec![]));
+        assert!(store.selection_keys_unordered().is_empty());
+    }
+
+    #[test]
+    fn selection_bookmarks_persist_with_the_document() {
+        let mut store = StrokeStore::default();
+        let key = store.insert_stroke(
+            Stroke::ShapeStroke(ShapeStroke::new(
+                Shape::Line(Line::new(na::vector![0.0, 0.0], na::vector![100.0, 0.0])),
+                Style::default(),
+            )),
+            None,
+        );
+        store.set_selected(key, true);
+        store.bookmark_selection("intro");
+
+        let mut loaded: StrokeStore =
+            serde_json::from_value(serde_json::to_value(&store).unwrap()).unwrap();
+        loaded.set_selected(key, false);
+
+        assert_eq!(loaded.select_bookmark("intro"), Some(vec![key]));
+    }
+
+    #[test]
+    fn explode_selection_separates_overlapping_strokes() {
+        let mut store = StrokeStore::default();
+        // two crossing diagonals
+        let keys = [
+            store.insert_stroke(
+                Stroke::ShapeStroke(ShapeStroke::new(
+                    Shape::Line(Line::new(na::vector![0.0, 0.0], na::vector![100.0, 100.0])),
+                    Style::default(),
+                )),
+                None,
+            ),
+            store.insert_stroke(
+                Stroke::ShapeStroke(ShapeStroke::new(
+                    Shape::Line(Line::new(na::vector![20.0, 100.0], na::vector![120.0, 0.0])),
+                    Style::default(),
+                )),
+                None,
+            ),
+        ];
+        store.set_selected_keys(&keys, true);
+
+        assert!(!store.explode_selection(10.0).is_empty());
+        let bounds = store.keyed_strokes_bounds(&keys);
+        assert!(!bounds[0]
+            .1
+            .loosened(4.9)
+            .intersects(&bounds[1].1.loosened(4.9)));
+    }
+
+    #[test]
+    fn explode_selection_spreads_strokes_with_the_same_center() {
+        let mut store = StrokeStore::default();
+        let line = Stroke::ShapeStroke(ShapeStroke::new(
+            Shape::Line(Line::new(na::vector![0.0, 0.0], na::vector![100.0, 0.0])),
+            Style::default(),
+        ));
+        let keys = [
+            store.insert_stroke(line.clone(), None),
+            store.insert_stroke(line, None),
+        ];
+        store.set_selected_keys(&keys, true);
+
+        assert_eq!(store.explode_selection(0.0).len(), 1);
+        let bounds = store.keyed_strokes_bounds(&keys);
+        assert!(!bounds[0].1.intersects(&bounds[1].1));
+    }
+
+    #[test]
+    fn explode_selection_leaves_separate_strokes_in_place() {
+        let mut store = StrokeStore::default();
+        let keys = [
+            store.insert_stroke(
+                Stroke::ShapeStroke(ShapeStroke::new(
+                    Shape::Line(Line::new(na::vector![0.0, 0.0], na::vector![100.0, 0.0])),
+                    Style::default(),
+                )),
+                None,
+            ),
+            store.insert_stroke(
+                Stroke::ShapeStroke(ShapeStroke::new(
+                    Shape::Line(Line::new(
+                        na::vector![0.0, 100.0],
+                        na::vector![100.0, 100.0],
+                    )),
+                    Style::default(),
+                )),
+                None,
+            ),
+        ];
+        store.set_selected_keys(&keys, true);
+        let bounds = store.keyed_strokes_bounds(&keys);
+
+        assert!(store.explode_selection(10.0).is_empty());
+        assert_eq!(store.keyed_strokes_bounds(&keys), bounds);
+    }
+
+    #[test]
+    fn resize_selection_with_nan_extents_leaves_strokes_unchanged() {
+        let mut store = StrokeStore::default();
+        let key = store.insert_stroke(
+            Stroke::ShapeStroke(ShapeStroke::new(
+                Shape::Line(Line::new(na::vector![0.0, 0.0], na::vector![100.0, 50.0])),
+                Style::default(),
+            )),
+            None,
+        );
+        store.set_selected(key, true);
+        let bounds = store.get_stroke_ref(key).unwrap().bounds();
+
+        let new_bounds = store.resize_selection(
+            na::vector![f64::NAN, 10.0],
+            ResizeAnchor::TopLeft,
+            0.0,
+            &HashSet::new(),
+        );
+
+        assert!(new_bounds.is_none());
+        assert_eq!(store.get_stroke_ref(key).unwrap().bounds(), bounds);
+        assert!(store.take_transform_dirty_keys().is_empty());
+    }
+
+    #[test]
+    fn select_neighbour_stroke_skips_hidden_strokes() {
+        let mut store = StrokeStore::default();
+        let [first, hidden, last] = [0.0, 50.0, 100.0].map(|y| {
+            store.insert_stroke(
+                Stroke::ShapeStroke(ShapeStroke::new(
+                    Shape::Line(Line::new(na::vector![0.0, y], na::vector![100.0, y])),
+                    Style::default(),
+                )),
+                None,
+            )
+        });
+        store.render_components.get_mut(hidden).unwrap().render = false;
+
+        // selecting moves the stroke to the top, so the hidden stroke is next when wrapping around
+        assert_eq!(store.select_neighbour_stroke(true), Some(first));
+        assert_eq!(store.select_neighbour_stroke(true), Some(last));
+        assert_eq!(store.selection_keys_unordered(), vec![last]);
+    }
 }
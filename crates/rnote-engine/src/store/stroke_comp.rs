@@ -1,8 +1,8 @@
 // Imports
 use super::render_comp::RenderCompState;
-use super::StrokeKey;
+use super::{BoundsChange, StrokeKey, StrokeTransformHook, TransformHook};
 use crate::engine::StrokeContent;
-use crate::strokes::{Content, Stroke};
+use crate::strokes::{BlendMode, Content, Stroke};
 use crate::{StrokeStore, WidgetFlags};
 use geo::intersects::Intersects;
 use geo::prelude::Contains;
@@ -11,8 +11,9 @@ use rnote_compose::penpath::Element;
 use rnote_compose::shapes::Shapeable;
 use rnote_compose::transform::Transformable;
 use rnote_compose::Color;
+use std::ops::Range;
 use std::sync::Arc;
-use tracing::error;
+use tracing::{error, warn};
 
 /// Systems that are related to the stroke components.
 impl StrokeStore {
@@ -115,6 +116,26 @@ impl StrokeStore {
         }
     }
 
+    /// Update only the hitboxes of the part of a brush stroke that was edited in place,
+    /// see [crate::strokes::BrushStroke::update_hitboxes_for_elements]. Other strokes update their entire geometry.
+    ///
+    /// The stroke then needs to update its rendering.
+    pub(crate) fn rebuild_hitbox_range(&mut self, key: StrokeKey, element_range: Range<usize>) {
+        if let Some(stroke) = Arc::make_mut(&mut self.stroke_components)
+            .get_mut(key)
+            .map(Arc::make_mut)
+        {
+            match stroke {
+                Stroke::BrushStroke(brushstroke) => {
+                    brushstroke.update_hitboxes_for_elements(element_range)
+                }
+                _ => stroke.update_geometry(),
+            }
+            self.key_tree.update_with_key(key, stroke.bounds());
+            self.set_rendering_dirty(key);
+        }
+    }
+
     /// Updates the strokes geometries.
     ///
     /// The strokes then need to update their rendering.
@@ -171,6 +192,17 @@ impl StrokeStore {
         Some(bounds)
     }
 
+    /// Generate the enclosing bounds for the given keys, skipping invalid and trashed keys.
+    ///
+    /// None if none of the keys are valid.
+    pub(crate) fn bounds_of_keys(&self, keys: &[StrokeKey]) -> Option<Aabb> {
+        keys.iter()
+            .filter(|&&key| !self.trashed(key).unwrap_or(true))
+            .filter_map(|&key| self.stroke_components.get(key))
+            .map(|stroke| stroke.bounds())
+            .reduce(|acc, bounds| acc.merged(&bounds))
+    }
+
     /// Collect all stroke bounds for the given keys.
     pub(crate) fn strokes_bounds(&self, keys: &[StrokeKey]) -> Vec<Aabb> {
         keys.iter()
@@ -178,6 +210,76 @@ impl StrokeStore {
             .collect::<Vec<Aabb>>()
     }
 
+    /// The bounds of the strokes together with their keys.
+    pub(crate) fn keyed_strokes_bounds(&self, keys: &[StrokeKey]) -> Vec<(StrokeKey, Aabb)> {
+        keys.iter()
+            .filter_map(|&key| Some((key, self.stroke_components.get(key)?.bounds())))
+            .collect()
+    }
+
+    /// Append copies of all not trashed strokes of the other store, translated by the offset, and select them.
+    ///
+    /// The copies get new keys and keep their layers, their relative chronological order and their tags.
+    /// All other strokes are deselected. Returns the keys of the appended strokes.
+    ///
+    /// The strokes then need to update their geometry and rendering.
+    pub(crate) fn append_strokes_from(
+        &mut self,
+        other: &StrokeStore,
+        offset: na::Vector2<f64>,
+    ) -> Vec<StrokeKey> {
+        let old_selection = self.selection_keys_unordered();
+        self.set_selected_keys(&old_selection, false);
+
+        let appended = other
+            .stroke_keys_as_rendered()
+            .into_iter()
+            .filter_map(|other_key| {
+                let mut stroke = (**other.stroke_components.get(other_key)?).clone();
+                stroke.translate(offset);
+                let layer = other
+                    .chrono_components
+                    .get(other_key)
+                    .map(|chrono_comp| chrono_comp.layer);
+                let key = self.insert_stroke(stroke, layer);
+                for tag in other.tags(other_key) {
+                    self.add_tag(key, tag.clone());
+                }
+                Some(key)
+            })
+            .collect::<Vec<StrokeKey>>();
+        self.set_selected_keys(&appended, true);
+        appended
+    }
+
+    /// The minimum gap between the bounds of the two strokes, zero if they overlap.
+    ///
+    /// None if either stroke doesn't exist.
+    pub(crate) fn distance_between_keys(&self, a: StrokeKey, b: StrokeKey) -> Option<f64> {
+        let a_bounds = self.stroke_components.get(a)?.bounds();
+        let b_bounds = self.stroke_components.get(b)?.bounds();
+        let gap = (a_bounds.mins.coords - b_bounds.maxs.coords)
+            .sup(&(b_bounds.mins.coords - a_bounds.maxs.coords))
+            .sup(&na::Vector2::zeros());
+        Some(gap.norm())
+    }
+
+    /// Compare the given previous bounds of strokes with their current bounds.
+    ///
+    /// Strokes that were removed in the meantime are skipped.
+    pub(crate) fn bounds_changes(&self, old_bounds: Vec<(StrokeKey, Aabb)>) -> Vec<BoundsChange> {
+        old_bounds
+            .into_iter()
+            .filter_map(|(key, old_bounds)| {
+                Some(BoundsChange {
+                    key,
+                    old_bounds,
+                    new_bounds: self.stroke_components.get(key)?.bounds(),
+                })
+            })
+            .collect()
+    }
+
     pub(crate) fn set_stroke_pos(&mut self, key: StrokeKey, pos: na::Vector2<f64>) {
         let Some(stroke) = Arc::make_mut(&mut self.stroke_components)
             .get_mut(key)
@@ -190,8 +292,19 @@ impl StrokeStore {
 
     /// Translate the strokes by the offset.
     ///
+    /// Non-finite offsets leave the strokes untouched. Returns whether the strokes were translated.
+    ///
     /// The strokes then need to update their geometry and rendering.
-    pub(crate) fn translate_strokes(&mut self, keys: &[StrokeKey], offset: na::Vector2<f64>) {
+    pub(crate) fn translate_strokes(
+        &mut self,
+        keys: &[StrokeKey],
+        offset: na::Vector2<f64>,
+    ) -> bool {
+        if !offset.iter().all(|v| v.is_finite()) {
+            warn!("Skipping translating strokes, offset {offset:?} is not finite.");
+            return false;
+        }
+        self.translate_instance_offsets(keys, offset);
         keys.iter().for_each(|&key| {
             if let Some(stroke) = Arc::make_mut(&mut self.stroke_components)
                 .get_mut(key)
@@ -201,9 +314,14 @@ impl StrokeStore {
                     // translate the stroke geometry
                     stroke.translate(offset);
                     self.key_tree.update_with_key(key, stroke.bounds());
+                    if let Some(hook) = self.transform_hook.as_mut() {
+                        (hook.0)(key, stroke);
+                    }
                 }
             }
+            self.update_selection_transform(key, |transform| transform.translate(offset));
         });
+        true
     }
 
     /// Translate the stroke rendering images.
@@ -242,6 +360,8 @@ impl StrokeStore {
         angle: f64,
         center: na::Point2<f64>,
     ) {
+        // instances can only mirror their source with an offset
+        self.make_instances_unique(keys);
         keys.iter().for_each(|&key| {
             if let Some(stroke) = Arc::make_mut(&mut self.stroke_components)
                 .get_mut(key)
@@ -251,11 +371,41 @@ impl StrokeStore {
                     // rotate the stroke geometry
                     stroke.rotate(angle, center);
                     self.key_tree.update_with_key(key, stroke.bounds());
+                    if let Some(hook) = self.transform_hook.as_mut() {
+                        (hook.0)(key, stroke);
+                    }
                 }
             }
+            self.update_selection_transform(key, |transform| transform.rotate(angle, center));
         });
     }
 
+    /// Set the blend mode of the images for the given keys, other strokes are left untouched.
+    ///
+    /// Returns the keys of the modified strokes.
+    pub(crate) fn set_blend_mode_for_strokes(
+        &mut self,
+        keys: &[StrokeKey],
+        blend_mode: BlendMode,
+    ) -> Vec<StrokeKey> {
+        keys.iter()
+            .copied()
+            .filter(|&key| {
+                if self
+                    .stroke_components
+                    .get(key)
+                    .map_or(true, |stroke| stroke.blend_mode() == blend_mode)
+                {
+                    return false;
+                }
+                Arc::make_mut(&mut self.stroke_components)
+                    .get_mut(key)
+                    .map(Arc::make_mut)
+                    .map_or(false, |stroke| stroke.set_blend_mode(blend_mode))
+            })
+            .collect()
+    }
+
     /// Change the stroke and text color for the given keys.
     ///
     /// The strokes then need to update their rendering.
@@ -397,6 +547,8 @@ impl StrokeStore {
     ///
     /// The strokes then need to update their rendering.
     pub(crate) fn scale_strokes(&mut self, keys: &[StrokeKey], scale: na::Vector2<f64>) {
+        // instances can only mirror their source with an offset
+        self.make_instances_unique(keys);
         keys.iter().for_each(|&key| {
             if let Some(stroke) = Arc::make_mut(&mut self.stroke_components)
                 .get_mut(key)
@@ -406,8 +558,12 @@ impl StrokeStore {
                     // rotate the stroke geometry
                     stroke.scale(scale);
                     self.key_tree.update_with_key(key, stroke.bounds());
+                    if let Some(hook) = self.transform_hook.as_mut() {
+                        (hook.0)(key, stroke);
+                    }
                 }
             }
+            self.update_selection_transform(key, |transform| transform.scale(scale));
         });
     }
 
@@ -445,9 +601,37 @@ impl StrokeStore {
         scale: na::Vector2<f64>,
         pivot: na::Vector2<f64>,
     ) {
-        self.translate_strokes(keys, -pivot);
-        self.scale_strokes(keys, scale);
-        self.translate_strokes(keys, pivot);
+        // instances can only mirror their source with an offset
+        self.make_instances_unique(keys);
+        keys.iter().for_each(|&key| {
+            if let Some(stroke) = Arc::make_mut(&mut self.stroke_components)
+                .get_mut(key)
+                .map(Arc::make_mut)
+            {
+                {
+                    // scale the stroke geometry in one pass, so the hook only sees the final geometry
+                    stroke.translate(-pivot);
+                    stroke.scale(scale);
+                    stroke.translate(pivot);
+                    self.key_tree.update_with_key(key, stroke.bounds());
+                    if let Some(hook) = self.transform_hook.as_mut() {
+                        (hook.0)(key, stroke);
+                    }
+                }
+            }
+            self.update_selection_transform(key, |transform| {
+                transform.translate(-pivot);
+                transform.scale(scale);
+                transform.translate(pivot);
+            });
+        });
+    }
+
+    /// Set the hook that gets invoked with every stroke right after its geometry was translated, rotated or scaled.
+    ///
+    /// None removes the hook.
+    pub(crate) fn set_transform_hook(&mut self, hook: Option<StrokeTransformHook>) {
+        self.transform_hook = hook.map(TransformHook);
     }
 
     /// Scale the stroke rendering images with a pivot.
@@ -749,3 +933,107 @@ impl StrokeStore {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::strokes::{BrushStroke, ShapeStroke, Stroke};
+    use crate::StrokeStore;
+    use p2d::bounding_volume::Aabb;
+    use rnote_compose::penpath::{Element, Segment};
+    use rnote_compose::shapes::{Line, Shape, Shapeable};
+    use rnote_compose::{PenPath, Style};
+
+    /// A lasso path enclosing the diagonal line from `origin` to `origin + (100, 100)`,
+    /// but not its entire bounds.
+    fn lasso_around_diagonal(origin: na::Vector2<f64>) -> Vec<Element> {
+        [
+            na::vector![-25.0, 5.0],
+            na::vector![5.0, -25.0],
+            na::vector![125.0, 95.0],
+            na::vector![95.0, 125.0],
+        ]
+        .into_iter()
+        .map(|pos| Element::new(origin + pos, 1.0))
+        .collect()
+    }
+
+    #[test]
+    fn lasso_select_brushstroke_after_translate() {
+        let mut store = StrokeStore::default();
+        let path = PenPath::new_w_segments(
+            Element::new(na::vector![0.0, 0.0], 1.0),
+            [Segment::LineTo {
+                end: Element::new(na::vector![100.0, 100.0], 1.0),
+            }],
+        );
+        let key = store.insert_stroke(
+            Stroke::BrushStroke(BrushStroke::from_penpath(path, Style::default())),
+            None,
+        );
+        let viewport = Aabb::new(na::point![-1000.0, -1000.0], na::point![1000.0, 1000.0]);
+        let offset = na::vector![300.0, 200.0];
+
+        assert_eq!(
+            store.strokes_hitboxes_contained_in_path_polygon(
+                &lasso_around_diagonal(na::Vector2::zeros()),
+                viewport
+            ),
+            vec![key]
+        );
+
+        store.translate_strokes(&[key], offset);
+
+        assert_eq!(
+            store.strokes_hitboxes_contained_in_path_polygon(
+                &lasso_around_diagonal(offset),
+                viewport
+            ),
+            vec![key]
+        );
+        assert!(store
+            .strokes_hitboxes_contained_in_path_polygon(
+                &lasso_around_diagonal(na::Vector2::zeros()),
+                viewport
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn mirror_shapestroke_keeps_width_and_bounds_finite() {
+        let mut store = StrokeStore::default();
+        let key = store.insert_stroke(
+            Stroke::ShapeStroke(ShapeStroke::new(
+                Shape::Line(Line::new(na::vector![0.0, 0.0], na::vector![100.0, 50.0])),
+                Style::default(),
+            )),
+            None,
+        );
+
+        for scale in [na::vector![-1.0, 1.0], na::vector![1.0, -1.0]] {
+            store.scale_strokes_with_pivot(&[key], scale, na::vector![50.0, 25.0]);
+            let Some(Stroke::ShapeStroke(shapestroke)) = store.get_stroke_ref(key) else {
+                panic!("the shape stroke is missing");
+            };
+            assert!(shapestroke.style.stroke_width().is_finite());
+            let bounds = shapestroke.bounds();
+            assert!(bounds.mins.coords.iter().all(|v| v.is_finite()));
+            assert!(bounds.maxs.coords.iter().all(|v| v.is_finite()));
+        }
+    }
+
+    #[test]
+    fn translate_strokes_with_infinite_offset_leaves_strokes_unchanged() {
+        let mut store = StrokeStore::default();
+        let key = store.insert_stroke(
+            Stroke::BrushStroke(BrushStroke::new(
+                Element::new(na::vector![10.0, 20.0], 1.0),
+                Style::default(),
+            )),
+            None,
+        );
+        let bounds = store.get_stroke_ref(key).unwrap().bounds();
+
+        assert!(!store.translate_strokes(&[key], na::vector![f64::INFINITY, 0.0]));
+        assert_eq!(store.get_stroke_ref(key).unwrap().bounds(), bounds);
+    }
+}
@@ -0,0 +1,53 @@
+// Imports
+use super::{StrokeKey, StrokeStore};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// User defined labels of a stroke, e.g. "todo" or "important".
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename = "tag_component")]
+pub struct TagComponent {
+    #[serde(rename = "tags")]
+    pub tags: Vec<String>,
+}
+
+/// Systems that are related to stroke tags.
+impl StrokeStore {
+    /// The tags of the given stroke. Empty if the stroke has no tags.
+    pub(crate) fn tags(&self, key: StrokeKey) -> &[String] {
+        self.tag_components
+            .get(key)
+            .map(|tag_comp| tag_comp.tags.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Add the tag to the given stroke.
+    ///
+    /// Returns false if the stroke does not exist or already has the tag.
+    pub(crate) fn add_tag(&mut self, key: StrokeKey, tag: String) -> bool {
+        if !self.stroke_components.contains_key(key) || self.tags(key).contains(&tag) {
+            return false;
+        }
+        let tag_components = Arc::make_mut(&mut self.tag_components);
+        match tag_components.get_mut(key) {
+            Some(tag_comp) => Arc::make_mut(tag_comp).tags.push(tag),
+            None => {
+                tag_components.insert(key, Arc::new(TagComponent { tags: vec![tag] }));
+            }
+        }
+        true
+    }
+
+    /// Select all strokes that have the given tag, across the entire document.
+    ///
+    /// Trashed strokes are never selected. Returns the newly selected keys.
+    pub(crate) fn select_strokes_by_tag(&mut self, tag: &str) -> Vec<StrokeKey> {
+        let tagged = self
+            .stroke_keys_as_rendered()
+            .into_iter()
+            .filter(|&key| self.tags(key).iter().any(|t| t == tag))
+            .collect::<Vec<StrokeKey>>();
+        self.set_selected_keys(&tagged, true);
+        tagged
+    }
+}
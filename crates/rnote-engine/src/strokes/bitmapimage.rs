@@ -1,6 +1,6 @@
 // Imports
 use super::resize::{calculate_resize_ratio, ImageSizeOption};
-use super::{Content, Stroke};
+use super::{BlendMode, Content, Stroke};
 use crate::document::Format;
 use crate::engine::import::{PdfImportPageSpacing, PdfImportPrefs};
 use crate::render;
@@ -29,6 +29,9 @@ pub struct BitmapImage {
     pub image: render::Image,
     #[serde(rename = "rectangle")]
     pub rectangle: Rectangle,
+    /// How the image is composited with the content below it.
+    #[serde(rename = "blend_mode")]
+    pub blend_mode: BlendMode,
 }
 
 impl Default for BitmapImage {
@@ -36,6 +39,7 @@ impl Default for BitmapImage {
         Self {
             image: render::Image::default(),
             rectangle: Rectangle::default(),
+            blend_mode: BlendMode::default(),
         }
     }
 }
@@ -123,7 +127,11 @@ impl BitmapImage {
             cuboid: p2d::shape::Cuboid::new(size * 0.5),
             transform,
         };
-        Ok(Self { image, rectangle })
+        Ok(Self {
+            image,
+            rectangle,
+            blend_mode: BlendMode::default(),
+        })
     }
 
     pub fn from_pdf_bytes(
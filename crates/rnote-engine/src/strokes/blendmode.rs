@@ -0,0 +1,44 @@
+// Imports
+use serde::{Deserialize, Serialize};
+
+/// How an image is composited with the content below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename = "blend_mode")]
+pub enum BlendMode {
+    #[serde(rename = "normal")]
+    Normal,
+    #[serde(rename = "multiply")]
+    Multiply,
+    #[serde(rename = "screen")]
+    Screen,
+    #[serde(rename = "overlay")]
+    Overlay,
+}
+
+impl Default for BlendMode {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+impl BlendMode {
+    /// The value of the Css `mix-blend-mode` property.
+    pub fn css_value(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Multiply => "multiply",
+            Self::Screen => "screen",
+            Self::Overlay => "overlay",
+        }
+    }
+
+    #[cfg(feature = "ui")]
+    pub(crate) fn to_gsk(self) -> gtk4::gsk::BlendMode {
+        match self {
+            Self::Normal => gtk4::gsk::BlendMode::Default,
+            Self::Multiply => gtk4::gsk::BlendMode::Multiply,
+            Self::Screen => gtk4::gsk::BlendMode::Screen,
+            Self::Overlay => gtk4::gsk::BlendMode::Overlay,
+        }
+    }
+}
@@ -14,6 +14,7 @@ use rnote_compose::style::Composer;
 use rnote_compose::transform::Transformable;
 use rnote_compose::{PenPath, Style};
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 use tracing::error;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,9 +24,10 @@ pub struct BrushStroke {
     pub path: PenPath,
     #[serde(default, rename = "style")]
     pub style: Style,
-    // since the path can have many hitboxes, we store them here and update them when the stroke geometry changes
+    // since the path can have many hitboxes, we store them here and update them when the stroke geometry changes.
+    // They are grouped by segment, so that they can be updated for parts of the path.
     #[serde(skip)]
-    hitboxes: Vec<Aabb>,
+    hitboxes: Vec<Vec<Aabb>>,
 }
 
 impl Content for BrushStroke {
@@ -233,7 +235,7 @@ impl Shapeable for BrushStroke {
     }
 
     fn hitboxes(&self) -> Vec<Aabb> {
-        self.hitboxes.clone()
+        self.hitboxes.iter().flatten().copied().collect()
     }
 
     fn outline_path(&self) -> kurbo::BezPath {
@@ -244,6 +246,11 @@ impl Shapeable for BrushStroke {
 impl Transformable for BrushStroke {
     fn translate(&mut self, offset: na::Vector2<f64>) {
         self.path.translate(offset);
+        // the hitboxes are kept in sync so that the stroke can be selected at its new location
+        self.hitboxes
+            .iter_mut()
+            .flatten()
+            .for_each(|hitbox| *hitbox = hitbox.translate(offset));
     }
     fn rotate(&mut self, angle: f64, center: na::Point2<f64>) {
         self.path.rotate(angle, center);
@@ -251,7 +258,8 @@ impl Transformable for BrushStroke {
     fn scale(&mut self, scale: na::Vector2<f64>) {
         self.path.scale(scale);
         // Using the geometric mean behaves the best when scaling non-uniformly.
-        let scale_scalar = (scale[0] * scale[1]).sqrt();
+        // The absolute value keeps the width valid when the stroke gets mirrored.
+        let scale_scalar = (scale[0] * scale[1]).abs().sqrt();
         self.style
             .set_stroke_width(self.style.stroke_width() * scale_scalar);
     }
@@ -290,14 +298,43 @@ impl BrushStroke {
         self.update_geometry();
     }
 
-    // internal method generating the current hitboxes.
-    fn gen_hitboxes_int(&self) -> Vec<Aabb> {
+    /// Update the hitboxes only for the segments that are connected to the elements in the given range,
+    /// after the elements were edited in place. Element `0` is the path start, element `i` the end of segment `i - 1`.
+    ///
+    /// Much faster than regenerating all hitboxes for long strokes.
+    /// If the number of segments changed, all hitboxes are regenerated to stay consistent with the path.
+    pub fn update_hitboxes_for_elements(&mut self, element_range: Range<usize>) {
+        let n_segments = self.path.segments.len();
+        if n_segments == 0 || self.hitboxes.len() != n_segments {
+            self.hitboxes = self.gen_hitboxes_int();
+            return;
+        }
+        let segment_range = element_range.start.saturating_sub(1).min(n_segments)
+            ..element_range.end.min(n_segments);
+        if segment_range.is_empty() {
+            return;
+        }
         let stroke_width = self.style.stroke_width();
+        let updated = self
+            .path
+            .hitboxes_for_segments(segment_range.clone())
+            .into_iter()
+            .map(|seg_hitboxes| loosened_hitboxes(seg_hitboxes, stroke_width))
+            .collect::<Vec<Vec<Aabb>>>();
+        self.hitboxes.splice(segment_range, updated);
+    }
 
+    // internal method generating the current hitboxes, grouped by segment.
+    fn gen_hitboxes_int(&self) -> Vec<Vec<Aabb>> {
+        let stroke_width = self.style.stroke_width();
+
+        if self.path.segments.is_empty() {
+            return vec![loosened_hitboxes(self.path.hitboxes(), stroke_width)];
+        }
         self.path
-            .hitboxes()
+            .hitboxes_for_segments(0..self.path.segments.len())
             .into_iter()
-            .map(|hb| hb.loosened(stroke_width * 0.5))
+            .map(|seg_hitboxes| loosened_hitboxes(seg_hitboxes, stroke_width))
             .collect()
     }
 
@@ -374,3 +411,11 @@ impl BrushStroke {
         Ok(image)
     }
 }
+
+/// Loosen the hitboxes by half the stroke width, so that they cover the drawn stroke.
+fn loosened_hitboxes(hitboxes: Vec<Aabb>, stroke_width: f64) -> Vec<Aabb> {
+    hitboxes
+        .into_iter()
+        .map(|hb| hb.loosened(stroke_width * 0.5))
+        .collect()
+}
@@ -1,5 +1,6 @@
 // Modules
 pub mod bitmapimage;
+pub mod blendmode;
 pub mod brushstroke;
 pub mod content;
 pub mod resize;
@@ -10,6 +11,7 @@ pub mod vectorimage;
 
 // Re-exports
 pub use bitmapimage::BitmapImage;
+pub use blendmode::BlendMode;
 pub use brushstroke::BrushStroke;
 pub use content::Content;
 pub use resize::Resize;
@@ -87,6 +87,10 @@ impl Shapeable for ShapeStroke {
 impl Transformable for ShapeStroke {
     fn translate(&mut self, offset: na::Vector2<f64>) {
         self.shape.translate(offset);
+        // the hitboxes are kept in sync so that the stroke can be selected at its new location
+        self.hitboxes
+            .iter_mut()
+            .for_each(|hitbox| *hitbox = hitbox.translate(offset));
     }
     fn rotate(&mut self, angle: f64, center: na::Point2<f64>) {
         self.shape.rotate(angle, center);
@@ -94,7 +98,8 @@ impl Transformable for ShapeStroke {
     fn scale(&mut self, scale: na::Vector2<f64>) {
         self.shape.scale(scale);
         // Using the geometric mean behaves the best when scaling non-uniformly.
-        let scale_scalar = (scale[0] * scale[1]).sqrt();
+        // The absolute value keeps the width valid when the stroke gets mirrored.
+        let scale_scalar = (scale[0] * scale[1]).abs().sqrt();
         self.style
             .set_stroke_width(self.style.stroke_width() * scale_scalar);
     }
@@ -4,7 +4,7 @@ use super::brushstroke::BrushStroke;
 use super::content::GeneratedContentImages;
 use super::shapestroke::ShapeStroke;
 use super::vectorimage::VectorImage;
-use super::{Content, TextStroke};
+use super::{BlendMode, Content, TextStroke};
 use crate::fileformats::xoppformat::{self, XoppColor};
 use crate::store::chrono_comp::StrokeLayer;
 use crate::{render, Engine};
@@ -37,13 +37,24 @@ pub enum Stroke {
 
 impl Content for Stroke {
     fn gen_svg(&self) -> Result<render::Svg, anyhow::Error> {
-        match self {
+        let mut svg = match self {
             Stroke::BrushStroke(brushstroke) => brushstroke.gen_svg(),
             Stroke::ShapeStroke(shapestroke) => shapestroke.gen_svg(),
             Stroke::TextStroke(textstroke) => textstroke.gen_svg(),
             Stroke::VectorImage(vectorimage) => vectorimage.gen_svg(),
             Stroke::BitmapImage(bitmapimage) => bitmapimage.gen_svg(),
+        }?;
+        let blend_mode = self.blend_mode();
+        if blend_mode != BlendMode::Normal {
+            let group = svg::node::element::Group::new()
+                .set(
+                    "style",
+                    format!("mix-blend-mode: {}", blend_mode.css_value()),
+                )
+                .add(svg::node::Blob::new(svg.svg_data));
+            svg.svg_data = rnote_compose::utils::svg_node_to_string(&group)?;
         }
+        Ok(svg)
     }
 
     fn gen_images(
@@ -214,6 +225,31 @@ impl Stroke {
         }
     }
 
+    /// How the stroke is composited with the content below it. Always normal for strokes that are not images.
+    pub fn blend_mode(&self) -> BlendMode {
+        match self {
+            Stroke::VectorImage(vectorimage) => vectorimage.blend_mode,
+            Stroke::BitmapImage(bitmapimage) => bitmapimage.blend_mode,
+            _ => BlendMode::Normal,
+        }
+    }
+
+    /// Set the blend mode of images, other strokes ignore it.
+    ///
+    /// Returns true if the stroke was modified and needs to update its rendering.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) -> bool {
+        let current = match self {
+            Stroke::VectorImage(vectorimage) => &mut vectorimage.blend_mode,
+            Stroke::BitmapImage(bitmapimage) => &mut bitmapimage.blend_mode,
+            _ => return false,
+        };
+        if *current == blend_mode {
+            return false;
+        }
+        *current = blend_mode;
+        true
+    }
+
     /// Invert the brightness of all colors of the stroke.
     ///
     /// Returns true if the stroke was modified and needs to update its rendering.
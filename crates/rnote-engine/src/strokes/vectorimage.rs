@@ -1,7 +1,7 @@
 // Imports
 use super::content::GeneratedContentImages;
 use super::resize::{calculate_resize_ratio, ImageSizeOption};
-use super::{Content, Stroke};
+use super::{BlendMode, Content, Stroke};
 use crate::document::Format;
 use crate::engine::import::{PdfImportPageSpacing, PdfImportPrefs};
 use crate::{render, Drawable};
@@ -31,6 +31,9 @@ pub struct VectorImage {
     pub intrinsic_size: na::Vector2<f64>,
     #[serde(rename = "rectangle")]
     pub rectangle: Rectangle,
+    /// How the image is composited with the content below it.
+    #[serde(rename = "blend_mode")]
+    pub blend_mode: BlendMode,
 }
 
 impl Default for VectorImage {
@@ -39,6 +42,7 @@ impl Default for VectorImage {
             svg_data: String::default(),
             intrinsic_size: na::Vector2::zeros(),
             rectangle: Rectangle::default(),
+            blend_mode: BlendMode::default(),
         }
     }
 }
@@ -201,6 +205,7 @@ impl VectorImage {
             svg_data,
             intrinsic_size,
             rectangle,
+            blend_mode: BlendMode::default(),
         })
     }
 
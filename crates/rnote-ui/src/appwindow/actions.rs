@@ -144,6 +144,9 @@ impl RnAppWindow {
         self.add_action(&action_export_doc_pages);
         let action_export_selection = gio::SimpleAction::new("export-selection", None);
         self.add_action(&action_export_selection);
+        let action_export_selection_strokes =
+            gio::SimpleAction::new("export-selection-strokes", None);
+        self.add_action(&action_export_selection_strokes);
         let action_clipboard_copy = gio::SimpleAction::new("clipboard-copy", None);
         self.add_action(&action_clipboard_copy);
         let action_clipboard_cut = gio::SimpleAction::new("clipboard-cut", None);
@@ -1002,6 +1005,34 @@ impl RnAppWindow {
             }
         ));
 
+        // Export selected strokes
+        action_export_selection_strokes.connect_activate(clone!(
+            #[weak(rename_to=appwindow)]
+            self,
+            move |_, _| {
+                glib::spawn_future_local(clone!(
+                    #[weak]
+                    appwindow,
+                    async move {
+                        let Some(canvas) = appwindow.active_tab_canvas() else {
+                            return;
+                        };
+
+                        if !canvas.engine_ref().nothing_selected() {
+                            dialogs::export::filechooser_export_selection_strokes(
+                                &appwindow, &canvas,
+                            )
+                            .await;
+                        } else {
+                            appwindow.overlays().dispatch_toast_error(&gettext(
+                                "Exporting selected strokes failed, nothing selected",
+                            ));
+                        }
+                    }
+                ));
+            }
+        ));
+
         // Clipboard copy
         action_clipboard_copy.connect_activate(clone!(#[weak(rename_to=appwindow)] self, move |_, _| {
             glib::spawn_future_local(clone!(#[weak] appwindow, async move {
@@ -342,6 +342,41 @@ impl RnCanvas {
         Ok(())
     }
 
+    /// Exports each selected stroke into a separate Svg file
+    /// `file_stem_name`: the stem name of the created files. This is extended by an enumeration of the stroke and
+    /// file extension overwrites existing files with the same name!
+    pub(crate) async fn export_selection_strokes_as_svg_files(
+        &self,
+        dir: &gio::File,
+        file_stem_name: &str,
+        export_prefs_override: Option<SelectionExportPrefs>,
+    ) -> anyhow::Result<()> {
+        if dir.query_file_type(gio::FileQueryInfoFlags::NONE, gio::Cancellable::NONE)
+            != gio::FileType::Directory
+        {
+            return Err(anyhow::anyhow!(
+                "Supplied target file `{dir:?}` is not a directory."
+            ));
+        }
+
+        let export_bytes_recv = self
+            .engine_ref()
+            .export_selection_strokes_as_svgs_bytes(export_prefs_override);
+        let export_bytes = export_bytes_recv.await??;
+
+        for (i, stroke_bytes) in export_bytes.into_iter().enumerate() {
+            crate::utils::create_replace_file_future(
+                stroke_bytes,
+                &dir.child(format!("{file_stem_name}_{i}.svg")),
+            )
+            .await?;
+        }
+
+        self.set_last_export_dir(Some(dir.clone()));
+
+        Ok(())
+    }
+
     /// exports and writes the engine state as json into the file.
     /// Only for debugging!
     pub(crate) async fn export_engine_state(&self, file: &gio::File) -> anyhow::Result<()> {
@@ -847,13 +847,13 @@ pub(crate) async fn dialog_export_selection_w_prefs(appwindow: &RnAppWindow, can
             .to_u32()
             .unwrap(),
     );
-    bitmap_scalefactor_row.set_sensitive(
-        initial_selection_export_prefs.export_format == SelectionExportFormat::Png
-            || initial_selection_export_prefs.export_format == SelectionExportFormat::Jpeg,
-    );
+    bitmap_scalefactor_row
+        .set_sensitive(initial_selection_export_prefs.export_format != SelectionExportFormat::Svg);
     bitmap_scalefactor_row.set_value(initial_selection_export_prefs.bitmap_scalefactor);
-    jpeg_quality_row
-        .set_sensitive(initial_selection_export_prefs.export_format == SelectionExportFormat::Jpeg);
+    jpeg_quality_row.set_sensitive(matches!(
+        initial_selection_export_prefs.export_format,
+        SelectionExportFormat::Jpeg | SelectionExportFormat::Avif
+    ));
     jpeg_quality_row.set_value(initial_selection_export_prefs.jpeg_quality as f64);
     margin_row.set_value(initial_selection_export_prefs.margin);
     export_file_label.set_label(&gettext("- no file selected -"));
@@ -978,12 +978,12 @@ pub(crate) async fn dialog_export_selection_w_prefs(appwindow: &RnAppWindow, can
             selected_file.replace(None);
 
             // Set the bitmap scalefactor sensitive only when exporting to a bitmap image
-            bitmap_scalefactor_row.set_sensitive(
-                export_format == SelectionExportFormat::Png
-                    || export_format == SelectionExportFormat::Jpeg,
-            );
-            // Set the jpeg quality pref only sensitive when jpeg is actually selected
-            jpeg_quality_row.set_sensitive(export_format == SelectionExportFormat::Jpeg);
+            bitmap_scalefactor_row.set_sensitive(export_format != SelectionExportFormat::Svg);
+            // Set the quality pref only sensitive when a lossy format is actually selected
+            jpeg_quality_row.set_sensitive(matches!(
+                export_format,
+                SelectionExportFormat::Jpeg | SelectionExportFormat::Avif
+            ));
         }
     ));
 
@@ -1165,6 +1165,28 @@ fn create_filedialog_export_selection(
             }
             filter.set_name(Some(&gettext("Jpeg")));
         }
+        SelectionExportFormat::WebP => {
+            if cfg!(target_os = "windows") {
+                filter.add_pattern("*.webp");
+            } else {
+                filter.add_mime_type("image/webp");
+            }
+            if cfg!(target_os = "macos") {
+                filter.add_suffix("webp");
+            }
+            filter.set_name(Some(&gettext("WebP")));
+        }
+        SelectionExportFormat::Avif => {
+            if cfg!(target_os = "windows") {
+                filter.add_pattern("*.avif");
+            } else {
+                filter.add_mime_type("image/avif");
+            }
+            if cfg!(target_os = "macos") {
+                filter.add_suffix("avif");
+            }
+            filter.set_name(Some(&gettext("Avif")));
+        }
     }
     let file_ext = selection_export_prefs.export_format.file_ext();
     let file_name = crate::utils::default_file_title_for_export(
@@ -1183,6 +1205,57 @@ fn create_filedialog_export_selection(
     filedialog
 }
 
+/// Let the user pick a directory and export every selected stroke into a separate Svg file in it.
+///
+/// The files are named after the document, extended by the index of the stroke.
+pub(crate) async fn filechooser_export_selection_strokes(
+    appwindow: &RnAppWindow,
+    canvas: &RnCanvas,
+) {
+    let filedialog = FileDialog::builder()
+        .title(gettext("Export Selected Strokes"))
+        .modal(true)
+        .accept_label(gettext("Export"))
+        .build();
+
+    filedialog.set_initial_folder(get_initial_folder_for_export(appwindow, canvas).as_ref());
+
+    let file_stem_name = crate::utils::default_file_title_for_export(
+        canvas.output_file(),
+        Some(&canvas::OUTPUT_FILE_NEW_TITLE),
+        None,
+    );
+
+    match filedialog.select_folder_future(Some(appwindow)).await {
+        Ok(dir) => {
+            appwindow.overlays().progressbar_start_pulsing();
+
+            if let Err(e) = canvas
+                .export_selection_strokes_as_svg_files(&dir, &file_stem_name, None)
+                .await
+            {
+                error!("Exporting selected strokes failed, Err: {e:?}");
+
+                appwindow
+                    .overlays()
+                    .dispatch_toast_error(&gettext("Exporting selected strokes failed"));
+                appwindow.overlays().progressbar_abort();
+            } else {
+                appwindow.overlays().dispatch_toast_text(
+                    &gettext("Exported selected strokes successfully"),
+                    crate::overlays::TEXT_TOAST_TIMEOUT_DEFAULT,
+                );
+                appwindow.overlays().progressbar_finish();
+            }
+        }
+        Err(e) => {
+            debug!(
+                "Did not export selected strokes (Error or dialog dismissed by user), Err: {e:?}"
+            );
+        }
+    }
+}
+
 pub(crate) async fn filechooser_export_engine_state(appwindow: &RnAppWindow, canvas: &RnCanvas) {
     let filter = FileFilter::new();
     // note : mimetypes are not supported with the native file picker on windows
@@ -19,6 +19,8 @@ mod imp {
         pub(crate) selectorstyle_intersectingpath_toggle: TemplateChild<ToggleButton>,
         #[template_child]
         pub(crate) resize_lock_aspectratio_togglebutton: TemplateChild<ToggleButton>,
+        #[template_child]
+        pub(crate) selection_frozen_togglebutton: TemplateChild<ToggleButton>,
     }
 
     #[glib::object_subclass]
@@ -174,6 +176,22 @@ impl RnSelectorPage {
                         .resize_lock_aspectratio = resize_lock_aspectratio_togglebutton.is_active();
                 }
             ));
+
+        imp.selection_frozen_togglebutton.connect_toggled(clone!(
+            #[weak]
+            appwindow,
+            move |selection_frozen_togglebutton| {
+                let Some(canvas) = appwindow.active_tab_canvas() else {
+                    return;
+                };
+
+                canvas
+                    .engine_mut()
+                    .pens_config
+                    .selector_config
+                    .selection_frozen = selection_frozen_togglebutton.is_active();
+            }
+        ));
     }
 
     pub(crate) fn refresh_ui(&self, active_tab: &RnCanvasWrapper) {
@@ -190,5 +208,7 @@ impl RnSelectorPage {
 
         imp.resize_lock_aspectratio_togglebutton
             .set_active(selector_config.resize_lock_aspectratio);
+        imp.selection_frozen_togglebutton
+            .set_active(selector_config.selection_frozen);
     }
 }